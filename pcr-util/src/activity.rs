@@ -0,0 +1,119 @@
+//! Latest comments of each issue, collected on request with `--latest-comments`.
+//!
+//! `gh issue list --json comments` only returns the first 100 comments of each issue, so long
+//! threads would show stale "latest" comments. Instead, the latest comments and the total comment
+//! count of the issues are queried separately through the GraphQL API, many issues per query.
+
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::*;
+
+use crate::config::{CommonConfig, CommonTriageConfig};
+use crate::triage_item::Author;
+use crate::{TriageItem, issue_source};
+
+const COMMENTS_FRAGMENT: &str = "
+fragment comments on IssueCommentConnection {
+  totalCount
+  nodes { author { login } body createdAt url }
+}";
+
+/// Comment activity of an issue.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IssueActivity {
+    pub comment_count: u64,
+    /// Oldest to newest.
+    pub latest_comments: Vec<Comment>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Comment {
+    pub author: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub url: String,
+    pub body: String,
+}
+
+/// Response format.
+#[derive(Deserialize)]
+struct ItemRepr {
+    comments: CommentsRepr,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentsRepr {
+    total_count: u64,
+    nodes: Vec<CommentRepr>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommentRepr {
+    /// `null` for deleted accounts.
+    author: Option<Author>,
+    body: String,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    url: String,
+}
+
+/// Collect the latest comments of each issue, if the triage config asks for it.
+///
+/// Recordings only contain the `gh issue list` response, so there are no comments to collect when
+/// replaying one.
+pub(crate) fn collect_if_requested(
+    config: &CommonConfig,
+    triage_config: &CommonTriageConfig,
+    issues: &mut [TriageItem],
+) -> EResult<()> {
+    let Some(count) = triage_config.latest_comments else {
+        return Ok(());
+    };
+    if triage_config.from_recording.is_some() {
+        warn!("Not collecting comments, recordings don't contain them");
+        return Ok(());
+    }
+
+    let _sp = span!(Level::INFO, "Collecting latest comments").entered();
+    info!("Downloading the latest {count} comments of {} issues via `gh` cli", issues.len());
+    let numbers = issues.iter().map(|issue| issue.number).collect::<Vec<_>>();
+    let mut items = issue_source::gh_api_graphql_batched::<ItemRepr>(
+        config.checkout()?,
+        &numbers,
+        |number| {
+            format!(
+                "issueOrPullRequest(number: {number}) {{ \
+                ... on Issue {{ comments(last: {count}) {{ ...comments }} }} \
+                ... on PullRequest {{ comments(last: {count}) {{ ...comments }} }} }}"
+            )
+        },
+        COMMENTS_FRAGMENT,
+    )
+    .wrap_err("failed to collect latest comments")?;
+    for issue in issues {
+        issue.activity = items.remove(&issue.number).map(|item| activity(item.comments));
+    }
+    Ok(())
+}
+
+fn activity(comments: CommentsRepr) -> IssueActivity {
+    IssueActivity {
+        comment_count: comments.total_count,
+        latest_comments: comments
+            .nodes
+            .into_iter()
+            .map(|comment| Comment {
+                author: comment.author.map_or_else(|| "ghost".to_owned(), |author| author.login),
+                created_at: comment.created_at,
+                url: comment.url,
+                body: comment.body,
+            })
+            .collect(),
+    }
+}
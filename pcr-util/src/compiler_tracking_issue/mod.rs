@@ -1,51 +1,52 @@
 mod cmd;
 mod markdown_stub;
 
-use std::fs;
-
-use color_eyre::eyre::Context;
-use tracing::*;
-
 use crate::EResult;
 use crate::config::{CommonConfig, CompilerTrackingIssueTriage};
-use crate::issue_metadata::{self, IssueMetadataRepr};
+use crate::feature_gates::FeatureGates;
+use crate::issue_source::IssueSource;
+use crate::markdown_stub::IssueField;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "compiler-tracking-issue";
 
 pub(crate) fn perform_triage(
     config: &CommonConfig,
     triage_config: &CompilerTrackingIssueTriage,
+    source: &dyn IssueSource,
 ) -> EResult<()> {
-    let compiler_tracking_issues = {
-        let _sp = span!(Level::INFO, "Collecting T-compiler-only tracking issues").entered();
-        let compiler_tracking_issues = cmd::compiler_tracking_issue_cmd(&config.repo_path)?;
-        let compiler_tracking_issues: Vec<IssueMetadataRepr> =
-            serde_json::from_slice(&compiler_tracking_issues)
-                .wrap_err("failed to deserialize JSON response as issue metadata")?;
-        let mut compiler_tracking_issues = issue_metadata::simplify_repr(compiler_tracking_issues);
-
-        // Intentionally sort by oldest to newest.
-        compiler_tracking_issues.sort_by(|a, b| a.number.cmp(&b.number));
-        compiler_tracking_issues
+    let taxonomy = TeamTaxonomy::load(config)?;
+    let query = cmd::compiler_tracking_issue_query(&taxonomy);
+    let spec = TriageSpec {
+        kind: TRIAGE_KIND,
+        description: "T-compiler-only tracking issues",
+        queries: std::slice::from_ref(&query),
     };
-
-    info!("T-compiler-only tracking issues count: {}", compiler_tracking_issues.len());
-    info!(
-        "Writing T-compiler-only tracking issues metadata json to `{}`",
-        triage_config.common.persist_path
-    );
-    let json = serde_json::to_vec_pretty(&compiler_tracking_issues)?;
-    fs::write(&triage_config.common.persist_path, &json).wrap_err_with(|| {
-        format!("failed to write response to `{}`", triage_config.common.persist_path)
-    })?;
-
-    info!("Writing markdown stub to `{}`", triage_config.common.markdown_stub_path);
-
-    let stub =
-        markdown_stub::render_markdown_stub(&triage_config.common, &compiler_tracking_issues)
-            .wrap_err("failed to render markdown stub")?;
-
-    fs::write(&triage_config.common.markdown_stub_path, &stub).wrap_err_with(|| {
-        format!("failed to write markdown stub to `{}`", triage_config.common.markdown_stub_path)
-    })?;
-
-    Ok(())
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |_| Ok(()),
+        |collected_at, compiler_tracking_issues| {
+            let mut feature_gates = FeatureGates::load_for_fields(
+                config,
+                &IssueField::tracking_issue(&triage_config.common),
+                compiler_tracking_issues,
+            )?;
+            if let Some(feature_gates) = &mut feature_gates {
+                feature_gates.collect_library_issues(config.checkout()?)?;
+            }
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                &query,
+                compiler_tracking_issues,
+                feature_gates.as_ref(),
+            )
+        },
+    )
 }
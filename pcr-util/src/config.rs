@@ -1,6 +1,11 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::{Args, Parser, Subcommand};
+use color_eyre::Result as EResult;
+use color_eyre::eyre::eyre;
 use serde::{Deserialize, Serialize};
+use time::Date;
+
+use crate::markdown_stub::IssueLayout;
 
 #[derive(Debug, Parser)]
 pub struct Config {
@@ -11,17 +16,31 @@ pub struct Config {
     pub cmd: Cmd,
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Clone, Args)]
 pub struct CommonConfig {
-    /// Path to a `rust-lang/rust` checkout.
+    /// Path to a `rust-lang/rust` checkout. Required by the triages, which query GitHub from it.
+    #[clap(long)]
+    pub repo_path: Option<Utf8PathBuf>,
+
+    /// TOML file listing team and working group labels. Defaults to a built-in list extended with
+    /// the labels from the checkout's `triagebot.toml`.
     #[clap(long)]
-    pub repo_path: Utf8PathBuf,
+    pub team_taxonomy: Option<Utf8PathBuf>,
 
     /// Default log level.
     #[clap(long, value_enum, default_value_t = LogLevel::Info)]
     pub log_level: LogLevel,
 }
 
+impl CommonConfig {
+    /// The `rust-lang/rust` checkout, for subcommands that can't do without one.
+    pub fn checkout(&self) -> EResult<&Utf8Path> {
+        self.repo_path
+            .as_deref()
+            .ok_or_else(|| eyre!("no `rust-lang/rust` checkout given, pass `--repo-path`"))
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 #[derive(clap::ValueEnum)]
 #[serde(rename_all = "kebab-case")]
@@ -37,6 +56,14 @@ pub enum Cmd {
     PHighTriage(PHighTriage),
     CompilerTrackingIssueTriage(CompilerTrackingIssueTriage),
     NoTeamTrackingIssueTriage(NoTeamTrackingIssueTriage),
+    RegressionTriage(RegressionTriage),
+    PrioritizeTriage(PrioritizeTriage),
+    NominationsTriage(NominationsTriage),
+    StalePrTriage(StalePrTriage),
+    CustomTriage(CustomTriage),
+    SnapshotDiff(SnapshotDiffConfig),
+    PHighTrend(PHighTrendConfig),
+    ExtractNotes(ExtractNotes),
 }
 
 impl Cmd {
@@ -45,15 +72,23 @@ impl Cmd {
             Cmd::PHighTriage(_) => "P-high triage",
             Cmd::CompilerTrackingIssueTriage(_) => "T-compiler tracking issue triage",
             Cmd::NoTeamTrackingIssueTriage(_) => "No-team tracking issue triage",
+            Cmd::RegressionTriage(_) => "Regression triage",
+            Cmd::PrioritizeTriage(_) => "I-prioritize triage",
+            Cmd::NominationsTriage(_) => "Nominations triage",
+            Cmd::StalePrTriage(_) => "Stale PR triage",
+            Cmd::CustomTriage(_) => "Custom triage",
+            Cmd::SnapshotDiff(_) => "Snapshot diff",
+            Cmd::PHighTrend(_) => "P-high trend report",
+            Cmd::ExtractNotes(_) => "Triage notes extraction",
         }
     }
 }
 
 #[derive(Debug, Args)]
 pub struct CommonTriageConfig {
-    /// Where to store the deserialized JSON response.
-    #[clap(long)]
-    pub persist_path: Utf8PathBuf,
+    /// Where to store the deserialized JSON response. Not needed with `--from-snapshot`.
+    #[clap(long, required_unless_present = "from_snapshot")]
+    pub persist_path: Option<Utf8PathBuf>,
 
     /// Markdown stub document title.
     #[clap(long)]
@@ -61,6 +96,66 @@ pub struct CommonTriageConfig {
     /// Where to output a Markdown issue review document stub.
     #[clap(long)]
     pub markdown_stub_path: Utf8PathBuf,
+
+    /// How to render each issue in the Markdown stub, defaults to a layout suitable for the triage
+    /// kind.
+    #[clap(long, value_enum)]
+    pub issue_layout: Option<IssueLayout>,
+
+    /// Template for the Markdown stub, see `stub-template.example.md.jinja`.
+    #[clap(long)]
+    pub template: Option<Utf8PathBuf>,
+
+    /// Notes store (see `extract-notes`) to show each issue's notes from its most recent triage.
+    #[clap(long)]
+    pub notes_path: Option<Utf8PathBuf>,
+
+    /// Maximum number of issues to collect, defaults to a limit suitable for the triage kind.
+    /// It is an error if more issues than the limit match, since the results would be truncated.
+    #[clap(long)]
+    pub limit: Option<u32>,
+
+    /// Also collect the comment count and this many latest comments of each issue, and show them
+    /// in the Markdown stub. Queries GitHub once per 50 issues.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..=100))]
+    pub latest_comments: Option<u32>,
+
+    /// Also collect the pull requests linked to each issue, and show them in the Markdown stub.
+    /// Queries GitHub once per 50 issues, more often if issues are cross-referenced over 100 times.
+    #[clap(long)]
+    pub linked_prs: bool,
+
+    /// For tracking-issue triages, also show the feature gates each issue tracks, as declared in
+    /// the checkout's `compiler/rustc_feature`. Requires a full `rust-lang/rust` checkout.
+    #[clap(long)]
+    pub feature_gates: bool,
+
+    /// For tracking-issue triages, also show where the checkout enables each feature gate and how
+    /// many UI tests mention it. Implies `--feature-gates`.
+    #[clap(long)]
+    pub feature_usage: bool,
+
+    /// For tracking-issue triages, also show the last commit and the number of commits in the
+    /// last year changing lines that mention each feature gate. Searches the checkout's history
+    /// once per feature gate, the whole history for those without commits in the last year.
+    /// Implies `--feature-gates`.
+    #[clap(long)]
+    pub feature_git_activity: bool,
+
+    /// Also record the collected issues in this SQLite database, keyed by triage kind and time.
+    #[clap(long)]
+    pub history_db: Option<Utf8PathBuf>,
+
+    /// Load issues from a recorded `gh issue list --json` or `gh pr list --json` response instead
+    /// of querying GitHub. The triage's search query isn't applied to the recording, so it must
+    /// be the response to that query.
+    #[clap(long, conflicts_with = "from_snapshot")]
+    pub from_recording: Option<Utf8PathBuf>,
+
+    /// Don't collect issues, only re-render the Markdown stub from a snapshot previously persisted
+    /// to `--persist-path`. Nothing is persisted then, so `--persist-path` can be left out.
+    #[clap(long)]
+    pub from_snapshot: Option<Utf8PathBuf>,
 }
 
 #[derive(Debug, Parser)]
@@ -80,3 +175,105 @@ pub struct NoTeamTrackingIssueTriage {
     #[command(flatten)]
     pub common: CommonTriageConfig,
 }
+
+/// Regressions, grouped by regression label and by whether they have a priority label.
+#[derive(Debug, Parser)]
+pub struct RegressionTriage {
+    #[command(flatten)]
+    pub common: CommonTriageConfig,
+}
+
+/// Issues labelled `I-prioritize`, grouped by the priority their labels suggest, with a slot to
+/// propose a priority ahead of the meeting.
+#[derive(Debug, Parser)]
+pub struct PrioritizeTriage {
+    #[command(flatten)]
+    pub common: CommonTriageConfig,
+}
+
+/// Issues and PRs nominated for the compiler team meeting and T-compiler backport nominations,
+/// grouped like the meeting agenda.
+#[derive(Debug, Parser)]
+pub struct NominationsTriage {
+    #[command(flatten)]
+    pub common: CommonTriageConfig,
+}
+
+/// PRs waiting on author or review, or blocked, bucketed by how long they have had that status.
+#[derive(Debug, Parser)]
+pub struct StalePrTriage {
+    #[command(flatten)]
+    pub common: CommonTriageConfig,
+}
+
+/// Triage declared in a TOML file, see `triage-kinds.example.toml`.
+#[derive(Debug, Parser)]
+pub struct CustomTriage {
+    /// TOML file declaring triage kinds.
+    #[clap(long)]
+    pub triage_kinds: Utf8PathBuf,
+    /// Name of the triage kind to perform.
+    #[clap(long)]
+    pub triage_kind: String,
+
+    #[command(flatten)]
+    pub common: CommonTriageConfig,
+}
+
+/// Report what changed between two snapshots persisted by the same kind of triage.
+#[derive(Debug, Parser)]
+pub struct SnapshotDiffConfig {
+    /// Snapshot persisted by the earlier triage run.
+    #[clap(long)]
+    pub old_snapshot: Utf8PathBuf,
+    /// Snapshot persisted by the later triage run.
+    #[clap(long)]
+    pub new_snapshot: Utf8PathBuf,
+
+    /// Markdown report document title.
+    #[clap(long)]
+    pub markdown_report_title: String,
+    /// Where to output the Markdown report.
+    #[clap(long)]
+    pub markdown_report_path: Utf8PathBuf,
+}
+
+/// Report how the P-high backlog changed over the P-high triage runs recorded in a history
+/// database (see `--history-db`).
+#[derive(Debug, Parser)]
+pub struct PHighTrendConfig {
+    /// History database the P-high triage runs were recorded in.
+    #[clap(long)]
+    pub history_db: Utf8PathBuf,
+
+    /// Markdown report document title.
+    #[clap(long)]
+    pub markdown_report_title: String,
+    /// Where to output the Markdown report.
+    #[clap(long)]
+    pub markdown_report_path: Utf8PathBuf,
+    /// Where to also output the trend as CSV.
+    #[clap(long)]
+    pub csv_path: Option<Utf8PathBuf>,
+}
+
+/// Extract triage notes from an edited Markdown stub into a notes store.
+///
+/// Notes are whatever replaced an issue's `**TODO**` placeholder. A `Decision: <keyword>` line in
+/// the notes, e.g. `Decision: close`, is recorded as the triage decision.
+#[derive(Debug, Parser)]
+pub struct ExtractNotes {
+    /// The Markdown stub after triage, with notes in place of the `**TODO**` placeholders.
+    #[clap(long)]
+    pub markdown_stub_path: Utf8PathBuf,
+    /// JSON notes store to add the notes to, created if it doesn't exist yet.
+    #[clap(long)]
+    pub notes_path: Utf8PathBuf,
+    /// When the triage took place (`YYYY-MM-DD`), defaults to today.
+    #[clap(long, value_parser = parse_date)]
+    pub reviewed_on: Option<Date>,
+}
+
+fn parse_date(s: &str) -> Result<Date, time::error::Parse> {
+    Date::parse(s, time::macros::format_description!("[year]-[month]-[day]"))
+}
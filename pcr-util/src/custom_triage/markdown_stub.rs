@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use super::triage_kind::{Section, TriageKind};
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::feature_gates::FeatureGates;
+use crate::markdown_stub::{IssueLayout, RenderCtxt};
+use crate::query::IssueQuery;
+use crate::team::TeamTaxonomy;
+
+pub(crate) struct ReviewInfo<'a> {
+    pub(crate) triage_kind: &'a TriageKind,
+    pub(crate) issues: &'a [TriageItem],
+}
+
+impl<'a> ReviewInfo<'a> {
+    pub(crate) fn new(triage_kind: &'a TriageKind, issues: &'a [TriageItem]) -> Self {
+        Self { triage_kind, issues }
+    }
+
+    /// Partition issues into the sections of the triage kind, each issue going into the first
+    /// section it matches.
+    pub(crate) fn partition_by_section(&'a self) -> Vec<(&'a Section, Vec<&'a TriageItem>)> {
+        let mut sections =
+            self.triage_kind.sections.iter().map(|section| (section, vec![])).collect::<Vec<_>>();
+        for issue in self.issues {
+            if let Some((_, issues)) =
+                sections.iter_mut().find(|(section, _)| section.matches(issue))
+            {
+                issues.push(issue);
+            }
+        }
+        sections
+    }
+}
+
+/// Group issues by their labels starting with `prefix`. An issue with several such labels appears
+/// in each of their groups, issues without any are grouped under `None`.
+fn group_by_label_prefix<'a>(
+    issues: &[&'a TriageItem],
+    prefix: &str,
+) -> BTreeMap<Option<&'a str>, Vec<&'a TriageItem>> {
+    let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for &issue in issues {
+        let mut labels = issue.labels.iter().filter(|l| l.starts_with(prefix)).peekable();
+        if labels.peek().is_none() {
+            groups.entry(None).or_default().push(issue);
+        }
+        for label in labels {
+            groups.entry(Some(label.as_str())).or_default().push(issue);
+        }
+    }
+    groups
+}
+
+pub(crate) fn render_markdown_stub<'a>(
+    config: &CommonTriageConfig,
+    taxonomy: &TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    query: &IssueQuery,
+    info: ReviewInfo<'a>,
+    feature_gates: Option<&FeatureGates>,
+) -> EResult<String> {
+    let layout = config.issue_layout.or(info.triage_kind.layout).unwrap_or(IssueLayout::FieldList);
+    let mut ctx =
+        RenderCtxt::new(config, taxonomy, collected_at, layout, &info.triage_kind.fields)?;
+    if let Some(feature_gates) = feature_gates {
+        ctx.set_feature_gates(feature_gates);
+    }
+    ctx.render_document_header()?;
+    ctx.render_query_link(query)?;
+
+    for (section, issues) in info.partition_by_section() {
+        let empty_message = section.empty_message.as_deref();
+        match &section.group_by_label_prefix {
+            // Without issues there are no groups, but the section and its message still belong in
+            // the stub.
+            Some(prefix) if !issues.is_empty() => {
+                for (label, issues) in group_by_label_prefix(&issues, prefix) {
+                    let heading = match label {
+                        Some(label) => format!("{} (`{label}`)", section.heading),
+                        None => format!("{} (no `{prefix}*` label)", section.heading),
+                    };
+                    ctx.render_section(&heading, None, empty_message, &issues)?;
+                }
+            }
+            _ => ctx.render_section(&section.heading, None, empty_message, &issues)?,
+        }
+    }
+
+    Ok(ctx.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_triage::triage_kind::TriageKinds;
+    use crate::test_util::{self, TestDir};
+    use crate::triage_item;
+
+    fn triage_kind() -> TriageKind {
+        let triage_kinds: TriageKinds = toml::from_str(
+            r#"
+            [[triage-kind]]
+            name = "test"
+            description = "Test issues"
+
+            [[triage-kind.section]]
+            heading = "Compiler"
+            all-labels = ["T-compiler"]
+            group-by-label-prefix = "A-"
+            empty-message = "No compiler issues"
+
+            [[triage-kind.section]]
+            heading = "Rest"
+            "#,
+        )
+        .unwrap();
+        triage_kinds.triage_kinds.into_iter().next().unwrap()
+    }
+
+    fn numbers(issues: &[&TriageItem]) -> Vec<u64> {
+        issues.iter().map(|issue| issue.number).collect()
+    }
+
+    #[test]
+    fn issues_go_into_the_first_matching_section() {
+        let triage_kind = triage_kind();
+        let issues = triage_item::simplify_repr(vec![
+            test_util::issue(1, &["T-compiler"], &[]),
+            test_util::issue(2, &["T-libs"], &[]),
+            test_util::issue(3, &["T-compiler", "A-diagnostics"], &[]),
+        ]);
+        let info = ReviewInfo::new(&triage_kind, &issues);
+
+        let sections = info
+            .partition_by_section()
+            .into_iter()
+            .map(|(section, issues)| (section.heading.as_str(), numbers(&issues)))
+            .collect::<Vec<_>>();
+        assert_eq!(sections, [("Compiler", vec![1, 3]), ("Rest", vec![2])]);
+    }
+
+    #[test]
+    fn groups_by_each_label_with_prefix() {
+        let issues = triage_item::simplify_repr(vec![
+            test_util::issue(1, &["A-diagnostics", "A-parser", "T-compiler"], &[]),
+            test_util::issue(2, &["T-compiler"], &[]),
+            test_util::issue(3, &["A-parser"], &[]),
+        ]);
+        let issues = issues.iter().collect::<Vec<_>>();
+
+        let groups = group_by_label_prefix(&issues, "A-")
+            .into_iter()
+            .map(|(label, issues)| (label, numbers(&issues)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            [(None, vec![2]), (Some("A-diagnostics"), vec![1]), (Some("A-parser"), vec![1, 3])]
+        );
+    }
+
+    #[test]
+    fn renders_empty_grouped_section() {
+        let dir = TestDir::new("custom-triage-empty-grouped-section");
+        let (_, triage_config) = test_util::configs(&dir);
+        let triage_kind = triage_kind();
+        let issues = triage_item::simplify_repr(vec![test_util::issue(2, &["T-libs"], &[])]);
+
+        let stub = render_markdown_stub(
+            &triage_config,
+            &TeamTaxonomy::builtin(),
+            OffsetDateTime::now_utc(),
+            &triage_kind.query(),
+            ReviewInfo::new(&triage_kind, &issues),
+            None,
+        )
+        .unwrap();
+
+        assert!(stub.contains("## Compiler\n\n**No compiler issues**"));
+        assert!(stub.contains("## Rest"));
+    }
+}
@@ -0,0 +1,50 @@
+mod markdown_stub;
+mod triage_kind;
+
+use tracing::*;
+
+use self::markdown_stub::ReviewInfo;
+use crate::EResult;
+use crate::config::{CommonConfig, CustomTriage};
+use crate::feature_gates::FeatureGates;
+use crate::issue_source::IssueSource;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &CustomTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let triage_kind = triage_kind::load(&triage_config.triage_kinds, &triage_config.triage_kind)?;
+    debug!("using triage kind: {:#?}", triage_kind);
+    let query = triage_kind.query();
+    let spec = TriageSpec {
+        kind: &triage_kind.name,
+        description: &triage_kind.description,
+        queries: std::slice::from_ref(&query),
+    };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |issues| {
+            triage_kind.sort(issues);
+            Ok(())
+        },
+        |collected_at, issues| {
+            let feature_gates = FeatureGates::load_for_fields(config, &triage_kind.fields, issues)?;
+            let review_info = ReviewInfo::new(&triage_kind, issues);
+            let taxonomy = TeamTaxonomy::load(config)?;
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                &query,
+                review_info,
+                feature_gates.as_ref(),
+            )
+        },
+    )
+}
@@ -0,0 +1,224 @@
+//! Triage kinds declared in a TOML file, e.g.
+//!
+//! ```toml
+//! [[triage-kind]]
+//! name = "p-high-no-assignee"
+//! description = "P-high issues without assignee"
+//! labels = ["P-high"]
+//! search = "no:assignee"
+//! sort = "least-recently-updated"
+//! layout = "table"
+//! fields = ["link", "last-updated", "labels"]
+//!
+//! [[triage-kind.section]]
+//! heading = "T-compiler"
+//! all-labels = ["T-compiler"]
+//! group-by-label-prefix = "A-"
+//!
+//! [[triage-kind.section]]
+//! heading = "Other teams"
+//! ```
+//!
+//! See `triage-kinds.example.toml` for a documented example.
+
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::{Context, eyre};
+use serde::Deserialize;
+
+use crate::TriageItem;
+use crate::markdown_stub::{IssueField, IssueLayout};
+use crate::query::IssueQuery;
+use crate::triage_item::label_matches;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct TriageKinds {
+    #[serde(rename = "triage-kind")]
+    pub(crate) triage_kinds: Vec<TriageKind>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct TriageKind {
+    /// Name to select this triage kind with `--triage-kind`.
+    pub(crate) name: String,
+    /// What kind of issues this triage collects, e.g. "P-high issues".
+    pub(crate) description: String,
+    /// Collect pull requests instead of issues.
+    #[serde(default)]
+    pub(crate) pull_requests: bool,
+    /// Issues must have all of these labels.
+    #[serde(default)]
+    pub(crate) labels: Vec<String>,
+    /// Additional GitHub search query.
+    pub(crate) search: Option<String>,
+    #[serde(default = "default_limit")]
+    pub(crate) limit: u32,
+    #[serde(default)]
+    pub(crate) sort: SortOrder,
+    /// How to render each issue, can be overridden with `--issue-layout`.
+    pub(crate) layout: Option<IssueLayout>,
+    /// Which fields to render for each issue, in order.
+    #[serde(default = "default_fields")]
+    pub(crate) fields: Vec<IssueField>,
+    /// Each issue goes into the first section it matches. Issues matching no section are left out.
+    #[serde(rename = "section")]
+    pub(crate) sections: Vec<Section>,
+}
+
+fn default_limit() -> u32 {
+    1000
+}
+
+fn default_fields() -> Vec<IssueField> {
+    IssueField::DEFAULT.to_vec()
+}
+
+#[derive(Debug, Default, Copy, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SortOrder {
+    #[default]
+    OldestFirst,
+    NewestFirst,
+    LeastRecentlyUpdated,
+    MostRecentlyUpdated,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Section {
+    pub(crate) heading: String,
+    /// Issues must have all of these labels.
+    #[serde(default)]
+    pub(crate) all_labels: Vec<String>,
+    /// Issues must have at least one of these labels, if any are given.
+    #[serde(default)]
+    pub(crate) any_labels: Vec<String>,
+    /// Issues must have none of these labels.
+    #[serde(default)]
+    pub(crate) no_labels: Vec<String>,
+    /// Whether issues must (or must not) have an assignee.
+    pub(crate) assigned: Option<bool>,
+    /// Further split the section by labels with this prefix, e.g. `A-`.
+    pub(crate) group_by_label_prefix: Option<String>,
+    /// Shown instead of issues if no issue matched this section.
+    pub(crate) empty_message: Option<String>,
+}
+
+impl Section {
+    /// Label patterns support a trailing `*`, see [`label_matches`].
+    pub(crate) fn matches(&self, issue: &TriageItem) -> bool {
+        let has_label = |pattern: &String| issue.labels.iter().any(|l| label_matches(l, pattern));
+        self.all_labels.iter().all(has_label)
+            && (self.any_labels.is_empty() || self.any_labels.iter().any(has_label))
+            && !self.no_labels.iter().any(has_label)
+            && self.assigned.is_none_or(|assigned| assigned == !issue.assignees.is_empty())
+    }
+}
+
+impl TriageKind {
+    pub(crate) fn query(&self) -> IssueQuery {
+        let query = if self.pull_requests {
+            IssueQuery::pull_requests(&self.description)
+        } else {
+            IssueQuery::new(&self.description)
+        };
+        let query = query.limit(self.limit);
+        let query = self.labels.iter().fold(query, |query, label| query.label(label));
+        match &self.search {
+            Some(search) => query.raw(search),
+            None => query,
+        }
+    }
+
+    pub(crate) fn sort(&self, issues: &mut [TriageItem]) {
+        match self.sort {
+            SortOrder::OldestFirst => issues.sort_by_key(|issue| issue.number),
+            SortOrder::NewestFirst => issues.sort_by_key(|issue| std::cmp::Reverse(issue.number)),
+            SortOrder::LeastRecentlyUpdated => issues.sort_by_key(|issue| issue.updated_at),
+            SortOrder::MostRecentlyUpdated => {
+                issues.sort_by_key(|issue| std::cmp::Reverse(issue.updated_at))
+            }
+        }
+    }
+}
+
+pub(crate) fn load(path: &Utf8Path, name: &str) -> EResult<TriageKind> {
+    let triage_kinds = fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read triage kinds from `{path}`"))?;
+    let triage_kinds: TriageKinds = toml::from_str(&triage_kinds)
+        .wrap_err_with(|| format!("failed to parse triage kinds from `{path}`"))?;
+    let available =
+        triage_kinds.triage_kinds.iter().map(|kind| kind.name.clone()).collect::<Vec<_>>();
+    triage_kinds.triage_kinds.into_iter().find(|kind| kind.name == name).ok_or_else(|| {
+        eyre!("no triage kind named `{name}` in `{path}`, available: {}", available.join(", "))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{test_util, triage_item};
+
+    fn section(toml: &str) -> Section {
+        toml::from_str(&format!("heading = \"Test\"\n{toml}")).unwrap()
+    }
+
+    fn issue(labels: &[&str], assignees: &[&str]) -> TriageItem {
+        triage_item::simplify_repr(vec![test_util::issue(1, labels, assignees)]).remove(0)
+    }
+
+    #[test]
+    fn section_matches_label_and_assignee_conditions() {
+        let prioritized = section(
+            r#"
+            all-labels = ["T-compiler", "A-*"]
+            any-labels = ["P-high", "P-critical"]
+            no-labels = ["S-blocked"]
+            "#,
+        );
+        assert!(prioritized.matches(&issue(&["T-compiler", "A-parser", "P-high"], &[])));
+        assert!(!prioritized.matches(&issue(&["T-compiler", "P-high"], &[])));
+        assert!(!prioritized.matches(&issue(&["T-compiler", "A-parser", "P-low"], &[])));
+        assert!(
+            !prioritized.matches(&issue(&["T-compiler", "A-parser", "P-high", "S-blocked"], &[]))
+        );
+
+        let unprioritized_unassigned = section("no-labels = [\"P-*\"]\nassigned = false");
+        assert!(unprioritized_unassigned.matches(&issue(&["T-compiler"], &[])));
+        assert!(!unprioritized_unassigned.matches(&issue(&["P-low"], &[])));
+        assert!(!unprioritized_unassigned.matches(&issue(&[], &["jane"])));
+        assert!(section(r#"assigned = true"#).matches(&issue(&[], &["jane"])));
+        assert!(section("").matches(&issue(&[], &[])));
+    }
+
+    #[test]
+    fn sorts_by_sort_order() {
+        let sorted = |sort| {
+            let triage_kind: TriageKind = toml::from_str(&format!(
+                "name = \"test\"\ndescription = \"Test\"\nsort = \"{sort}\"\nsection = []"
+            ))
+            .unwrap();
+            let mut issues = triage_item::simplify_repr(vec![
+                test_util::issue(1, &[], &[]),
+                test_util::issue(2, &[], &[]),
+                test_util::issue(3, &[], &[]),
+            ]);
+            issues[0].updated_at = datetime!(2024-03-01 0:00 UTC);
+            issues[1].updated_at = datetime!(2024-01-01 0:00 UTC);
+            issues[2].updated_at = datetime!(2024-02-01 0:00 UTC);
+            issues.reverse();
+            triage_kind.sort(&mut issues);
+            issues.iter().map(|issue| issue.number).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted("oldest-first"), [1, 2, 3]);
+        assert_eq!(sorted("newest-first"), [3, 2, 1]);
+        assert_eq!(sorted("least-recently-updated"), [2, 3, 1]);
+        assert_eq!(sorted("most-recently-updated"), [1, 3, 2]);
+    }
+}
@@ -0,0 +1,39 @@
+mod parse;
+
+use std::fs;
+
+use color_eyre::eyre::Context;
+use time::OffsetDateTime;
+use tracing::*;
+
+use crate::EResult;
+use crate::config::ExtractNotes;
+use crate::notes::{NotesStore, TriageNote};
+
+pub(crate) fn perform_extract(extract_config: &ExtractNotes) -> EResult<()> {
+    let reviewed_on =
+        extract_config.reviewed_on.unwrap_or_else(|| OffsetDateTime::now_utc().date());
+
+    let stub = fs::read_to_string(&extract_config.markdown_stub_path).wrap_err_with(|| {
+        format!("failed to read markdown stub `{}`", extract_config.markdown_stub_path)
+    })?;
+    let issues = parse::parse_stub(&stub);
+    info!("Issues with triage notes count: {}", issues.len());
+
+    let mut store = NotesStore::load_or_default(&extract_config.notes_path)?;
+    for issue in issues {
+        debug!("#{}: decision {:?}", issue.number, issue.decision);
+        store.insert(
+            issue.number,
+            TriageNote {
+                reviewed_on,
+                title: issue.title,
+                decision: issue.decision,
+                notes: issue.notes,
+            },
+        );
+    }
+
+    info!("Writing triage notes to `{}`", extract_config.notes_path);
+    store.save(&extract_config.notes_path)
+}
@@ -0,0 +1,194 @@
+//! Extracting triage notes from an edited Markdown stub.
+//!
+//! Each issue starts at a `### #<number>: <title>` heading and ends at the next heading of the
+//! same or a higher level. Its notes follow the [`NOTES_MARKER`], or if that got lost while
+//! editing, the first blank line after the issue fields.
+
+use itertools::Itertools as _;
+
+use crate::markdown_stub::{NOTES_MARKER, NOTES_PLACEHOLDER, PROPOSED_PRIORITY};
+
+pub(crate) struct IssueNotes {
+    pub(crate) number: u64,
+    pub(crate) title: String,
+    pub(crate) decision: Option<String>,
+    pub(crate) notes: String,
+}
+
+/// Extract notes of each issue in `stub`. Placeholders that weren't filled in are left out, and so
+/// are issues with nothing but placeholders in their notes.
+pub(crate) fn parse_stub(stub: &str) -> Vec<IssueNotes> {
+    let mut issues = vec![];
+    let mut current: Option<(u64, &str, Vec<&str>)> = None;
+    for line in stub.lines() {
+        if is_section_end(line) {
+            issues.extend(
+                current
+                    .take()
+                    .and_then(|(number, title, lines)| issue_notes(number, title, &lines)),
+            );
+            current = parse_issue_heading(line).map(|(number, title)| (number, title, vec![]));
+        } else if let Some((_, _, lines)) = &mut current {
+            lines.push(line);
+        }
+    }
+    issues.extend(current.and_then(|(number, title, lines)| issue_notes(number, title, &lines)));
+    issues
+}
+
+fn is_section_end(line: &str) -> bool {
+    ["# ", "## ", "### "].iter().any(|prefix| line.starts_with(prefix))
+}
+
+/// Parse `### #<number>: <title>`.
+fn parse_issue_heading(line: &str) -> Option<(u64, &str)> {
+    let (number, title) = line.strip_prefix("### #")?.split_once(':')?;
+    Some((number.trim().parse().ok()?, title.trim()))
+}
+
+fn issue_notes(number: u64, title: &str, lines: &[&str]) -> Option<IssueNotes> {
+    let notes_start = match lines.iter().position(|line| line.trim() == NOTES_MARKER) {
+        Some(marker) => marker + 1,
+        None => {
+            let fields_start = lines.iter().position(|line| !line.trim().is_empty())?;
+            fields_start + lines[fields_start..].iter().position(|line| line.trim().is_empty())?
+        }
+    };
+    let unfilled_priority = format!("{PROPOSED_PRIORITY} {NOTES_PLACEHOLDER}");
+    let notes = lines[notes_start..]
+        .iter()
+        .filter(|line| ![NOTES_PLACEHOLDER, &unfilled_priority].contains(&line.trim()))
+        .join("\n")
+        .trim()
+        .to_owned();
+    if notes.is_empty() {
+        return None;
+    }
+
+    Some(IssueNotes { number, title: title.to_owned(), decision: decision(&notes), notes })
+}
+
+/// Find a `Decision: <keyword>` line and return the lowercased keyword.
+fn decision(notes: &str) -> Option<String> {
+    notes.lines().find_map(|line| {
+        // Allow e.g. `- **Decision**: close` or `**Decision:** keep.`
+        let line = line.trim().trim_start_matches(['-', '*', ' ']);
+        let (key, value) = line.split_once(':')?;
+        if !key.trim_end_matches('*').trim().eq_ignore_ascii_case("decision") {
+            return None;
+        }
+        let keyword = value.trim_start_matches(['*', ' ']).split_whitespace().next()?;
+        let keyword = keyword.trim_matches(|c: char| !c.is_alphanumeric() && c != '-');
+        (!keyword.is_empty()).then(|| keyword.to_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Number, decision and notes of each issue with notes in `stub`.
+    fn parse(stub: &str) -> Vec<(u64, Option<String>, String)> {
+        parse_stub(stub)
+            .into_iter()
+            .map(|issue| (issue.number, issue.decision, issue.notes))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_notes_after_marker() {
+        let stub = "\
+# P-high issues
+
+## T-compiler
+
+### #1: First issue
+
+- Link: <https://github.com/rust-lang/rust/issues/1>
+
+<!-- notes -->
+Still reproduces.
+
+Ping the assignee.
+
+### #2: Second issue
+
+- Link: <https://github.com/rust-lang/rust/issues/2>
+
+<!-- notes -->
+Fixed by #3.
+
+- **Decision**: close.
+";
+        assert_eq!(
+            parse(stub),
+            [
+                (1, None, "Still reproduces.\n\nPing the assignee.".to_owned()),
+                (2, Some("close".to_owned()), "Fixed by #3.\n\n- **Decision**: close.".to_owned()),
+            ]
+        );
+        assert_eq!(parse_stub(stub)[1].title, "Second issue");
+    }
+
+    #[test]
+    fn falls_back_to_first_blank_line_without_marker() {
+        let stub = "\
+### #1: Issue
+
+- Link: <https://github.com/rust-lang/rust/issues/1>
+- Labels: `T-compiler`
+
+Notes without marker.
+";
+        assert_eq!(parse(stub), [(1, None, "Notes without marker.".to_owned())]);
+    }
+
+    #[test]
+    fn leaves_out_unfilled_placeholders() {
+        let stub = "\
+### #1: Only placeholders
+
+<!-- notes -->
+Proposed priority: **TODO**
+
+**TODO**
+
+### #2: Partly filled in
+
+<!-- notes -->
+Proposed priority: **TODO**
+
+Needs a bisection.
+
+**TODO**
+";
+        assert_eq!(parse(stub), [(2, None, "Needs a bisection.".to_owned())]);
+    }
+
+    #[test]
+    fn notes_end_at_next_heading_up_to_level_three() {
+        let notes_followed_by = |heading: &str| {
+            let stub = format!("### #1: Issue\n\n<!-- notes -->\nNotes.\n\n{heading}\n\nMore.\n");
+            parse(&stub).into_iter().map(|(_, _, notes)| notes).collect::<Vec<_>>()
+        };
+        assert_eq!(notes_followed_by("# Title"), ["Notes."]);
+        assert_eq!(notes_followed_by("## Section"), ["Notes."]);
+        assert_eq!(notes_followed_by("### Not an issue"), ["Notes."]);
+        assert_eq!(notes_followed_by("#### Details"), ["Notes.\n\n#### Details\n\nMore."]);
+    }
+
+    #[test]
+    fn parses_decision_keywords() {
+        for (notes, expected) in [
+            ("- **Decision**: close.", Some("close")),
+            ("**Decision:** keep", Some("keep")),
+            ("Decision: Re-prioritize to P-medium", Some("re-prioritize")),
+            ("Some context.\n* decision: downgrade", Some("downgrade")),
+            ("Decision:", None),
+            ("No decision: yet", None),
+            ("Undecided.", None),
+        ] {
+            assert_eq!(decision(notes).as_deref(), expected, "notes: {notes:?}");
+        }
+    }
+}
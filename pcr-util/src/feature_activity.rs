@@ -0,0 +1,99 @@
+//! Recent git activity on code mentioning a feature gate in the `rust-lang/rust` checkout,
+//! collected on request with `--feature-git-activity`. A tracking issue with a quiet GitHub thread
+//! can still have actively changing code.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::{Context, eyre};
+use serde::Serialize;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::*;
+
+#[derive(Serialize)]
+pub(crate) struct FeatureActivity {
+    /// The most recent commit adding or removing a line that mentions the feature gate.
+    pub(crate) last_commit: Option<Commit>,
+    /// Number of such commits in the last year.
+    pub(crate) commits_last_year: usize,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Commit {
+    pub(crate) author: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub(crate) date: OffsetDateTime,
+}
+
+impl FeatureActivity {
+    /// E.g. "last changed on 2024-11-06 by Jane Doe, 12 commits in the last year".
+    pub(crate) fn describe(&self) -> String {
+        match &self.last_commit {
+            Some(commit) => format!(
+                "last changed on {} by {}, {} commits in the last year",
+                commit.date.date(),
+                commit.author,
+                self.commits_last_year
+            ),
+            None => "no commits found".to_owned(),
+        }
+    }
+}
+
+/// Looks up the git activity of each of the `features` in the checkout's history.
+///
+/// Searching the history for changed lines is slow: each feature searches the last year of
+/// history, and features without commits in the last year search the whole history.
+pub(crate) fn collect(
+    repo_path: &Utf8Path,
+    features: &BTreeSet<&str>,
+) -> EResult<BTreeMap<String, FeatureActivity>> {
+    let _sp = span!(Level::INFO, "Collecting feature gate git activity").entered();
+    info!("Searching git history of `{repo_path}` for {} feature gates", features.len());
+    features
+        .iter()
+        .map(|&feature| {
+            let activity = feature_activity(repo_path, feature).wrap_err_with(|| {
+                format!("failed to collect git activity of feature `{feature}`")
+            })?;
+            Ok((feature.to_owned(), activity))
+        })
+        .collect()
+}
+
+fn feature_activity(repo_path: &Utf8Path, feature: &str) -> EResult<FeatureActivity> {
+    debug!("Searching git history for feature `{feature}`");
+    let last_year = git_log_mentioning(repo_path, feature, &["--since=1.year.ago"])?;
+    let commits_last_year = last_year.len();
+    // Only search the whole history if the feature is quiet.
+    let last_commit = match last_year.into_iter().next() {
+        Some(commit) => Some(commit),
+        None => git_log_mentioning(repo_path, feature, &["-1"])?.into_iter().next(),
+    };
+    Ok(FeatureActivity { last_commit, commits_last_year })
+}
+
+/// Commits, newest first, with added or removed lines mentioning `feature` as a whole word.
+fn git_log_mentioning(repo_path: &Utf8Path, feature: &str, args: &[&str]) -> EResult<Vec<Commit>> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_path);
+    cmd.args(["log", "--no-merges", "--format=%aI%x09%an"]);
+    cmd.arg(format!("-G(^|[^A-Za-z0-9_]){feature}([^A-Za-z0-9_]|$)"));
+    cmd.args(args);
+    let res = cmd.output().wrap_err("failed to run `git log`")?;
+    if !res.status.success() {
+        return Err(eyre!("`git log` failed: {}", String::from_utf8_lossy(&res.stderr)));
+    }
+
+    String::from_utf8_lossy(&res.stdout)
+        .lines()
+        .map(|line| {
+            let (date, author) =
+                line.split_once('\t').ok_or_else(|| eyre!("unexpected `git log` line `{line}`"))?;
+            Ok(Commit { author: author.to_owned(), date: OffsetDateTime::parse(date, &Rfc3339)? })
+        })
+        .collect()
+}
@@ -0,0 +1,402 @@
+//! Feature gates declared in the `rust-lang/rust` checkout, to tell which feature each tracking
+//! issue tracks.
+//!
+//! The features are declared with `declare_features!` in `compiler/rustc_feature/src/`, one tuple
+//! per feature, e.g.
+//!
+//! ```text
+//! (unstable, abi_avr_interrupt, "1.45.0", Some(69664)),
+//! (removed, advanced_slice_patterns, "1.42.0", Some(62254),
+//!  Some("merged into `#![feature(slice_patterns)]`"), 1234),
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::{Context, eyre};
+use tracing::*;
+
+use crate::TriageItem;
+use crate::config::CommonConfig;
+use crate::feature_activity::{self, FeatureActivity};
+use crate::feature_usage::{self, FeatureUsage, walk_rust_files};
+use crate::markdown_stub::IssueField;
+
+const FEATURE_FILES: &[&str] = &[
+    "compiler/rustc_feature/src/unstable.rs",
+    "compiler/rustc_feature/src/accepted.rs",
+    "compiler/rustc_feature/src/removed.rs",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum FeatureStatus {
+    Unstable,
+    Incomplete,
+    Internal,
+    Accepted,
+    Removed,
+}
+
+impl FeatureStatus {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "unstable" => FeatureStatus::Unstable,
+            "incomplete" => FeatureStatus::Incomplete,
+            "internal" => FeatureStatus::Internal,
+            "accepted" => FeatureStatus::Accepted,
+            "removed" => FeatureStatus::Removed,
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            FeatureStatus::Unstable => "unstable",
+            FeatureStatus::Incomplete => "incomplete",
+            FeatureStatus::Internal => "internal",
+            FeatureStatus::Accepted => "accepted",
+            FeatureStatus::Removed => "removed",
+        }
+    }
+}
+
+pub(crate) struct FeatureGate {
+    pub(crate) name: String,
+    pub(crate) status: FeatureStatus,
+    /// Version the feature was added, accepted or removed in, depending on its status.
+    /// `CURRENT_RUSTC_VERSION` if that is the version currently being developed.
+    pub(crate) since: String,
+}
+
+impl FeatureGate {
+    /// E.g. "unstable since 1.45.0" or "removed in 1.42.0".
+    pub(crate) fn describe_status(&self) -> String {
+        let version = match self.since.as_str() {
+            "CURRENT_RUSTC_VERSION" => "the current version",
+            version => version,
+        };
+        match self.status {
+            FeatureStatus::Unstable | FeatureStatus::Incomplete | FeatureStatus::Internal => {
+                format!("{} since {version}", self.status.name())
+            }
+            FeatureStatus::Accepted | FeatureStatus::Removed => {
+                format!("{} in {version}", self.status.name())
+            }
+        }
+    }
+}
+
+/// Feature gates by tracking issue number. Several features can share a tracking issue.
+#[derive(Default)]
+pub(crate) struct FeatureGates {
+    by_issue: BTreeMap<u64, Vec<FeatureGate>>,
+    /// Tracking issues referenced by `issue = "..."` in `library/`, i.e. by `#[unstable]`,
+    /// `#[rustc_const_unstable]` and the like. Library features aren't declared in
+    /// `rustc_feature`. Only collected for [`FeatureGates::closing_reason`].
+    library_issues: BTreeSet<u64>,
+    /// In-tree usages by feature name, if collected.
+    usage: BTreeMap<String, FeatureUsage>,
+    /// Git activity by feature name, if collected.
+    activity: BTreeMap<String, FeatureActivity>,
+}
+
+impl FeatureGates {
+    pub(crate) fn load(repo_path: &Utf8Path) -> EResult<Self> {
+        let mut gates = FeatureGates::default();
+        for file in FEATURE_FILES {
+            let path = repo_path.join(file);
+            info!("Loading feature gates from `{path}`");
+            let source = fs::read_to_string(&path)
+                .wrap_err_with(|| format!("failed to read feature gates from `{path}`"))?;
+            for (gate, issue) in parse_features(&source)
+                .wrap_err_with(|| format!("failed to parse feature gates in `{path}`"))?
+            {
+                if let Some(issue) = issue {
+                    gates.by_issue.entry(issue).or_default().push(gate);
+                }
+            }
+        }
+        Ok(gates)
+    }
+
+    /// Collect the tracking issues of unstable library features, which
+    /// [`FeatureGates::closing_reason`] needs. Walks all of `library/`.
+    pub(crate) fn collect_library_issues(&mut self, repo_path: &Utf8Path) -> EResult<()> {
+        let library = repo_path.join("library");
+        info!("Collecting tracking issues of unstable library features in `{library}`");
+        walk_rust_files(&library, &mut |_, source| {
+            self.library_issues.extend(issue_attribute_values(source));
+        })
+        .wrap_err_with(|| format!("failed to collect tracking issues in `{library}`"))
+    }
+
+    /// Feature gates tracked by issue `number`.
+    pub(crate) fn for_issue(&self, number: u64) -> &[FeatureGate] {
+        self.by_issue.get(&number).map_or(&[], Vec::as_slice)
+    }
+
+    /// Load the feature gates, and collect whatever else about the features tracked by `issues`
+    /// the rendered `fields` show. `None` if none of the `fields` need the feature gates, so the
+    /// checkout isn't read at all.
+    pub(crate) fn load_for_fields(
+        config: &CommonConfig,
+        fields: &[IssueField],
+        issues: &[TriageItem],
+    ) -> EResult<Option<Self>> {
+        if !fields.iter().any(|field| field.needs_feature_gates()) {
+            return Ok(None);
+        }
+        let repo_path = config.checkout()?;
+        let mut gates = Self::load(repo_path)?;
+        let features = issues
+            .iter()
+            .flat_map(|issue| gates.for_issue(issue.number))
+            .map(|gate| gate.name.as_str())
+            .collect();
+        let usage = fields
+            .iter()
+            .any(|field| matches!(field, IssueField::FeatureUsage))
+            .then(|| feature_usage::scan(repo_path, &features))
+            .transpose()?;
+        let activity = fields
+            .iter()
+            .any(|field| matches!(field, IssueField::FeatureActivity))
+            .then(|| feature_activity::collect(repo_path, &features))
+            .transpose()?;
+        gates.usage = usage.unwrap_or_default();
+        gates.activity = activity.unwrap_or_default();
+        Ok(Some(gates))
+    }
+
+    /// In-tree usage of feature `name`, if collected.
+    pub(crate) fn usage(&self, name: &str) -> Option<&FeatureUsage> {
+        self.usage.get(name)
+    }
+
+    /// Git activity on code mentioning feature `name`, if collected.
+    pub(crate) fn activity(&self, name: &str) -> Option<&FeatureActivity> {
+        self.activity.get(name)
+    }
+
+    /// Why tracking issue `number` could be closed, if it looks stale: all its features are
+    /// accepted or removed. Requires [`FeatureGates::collect_library_issues`].
+    pub(crate) fn closing_reason(&self, number: u64) -> Option<String> {
+        let gates = self.for_issue(number);
+        let still_unstable = gates.iter().any(|gate| {
+            matches!(
+                gate.status,
+                FeatureStatus::Unstable | FeatureStatus::Incomplete | FeatureStatus::Internal
+            )
+        });
+        if gates.is_empty() || still_unstable || self.library_issues.contains(&number) {
+            return None;
+        }
+        let gates = gates
+            .iter()
+            .map(|gate| format!("`{}` {}", gate.name, gate.describe_status()))
+            .collect::<Vec<_>>();
+        Some(format!("All features are accepted or removed: {}", gates.join(", ")))
+    }
+
+    /// Whether a feature gate or a `library/` unstable attribute refers to tracking issue `number`.
+    /// Tracking issues for something other than a feature, e.g. a `-Z` flag, a lint or a target
+    /// feature, aren't referenced, so this alone doesn't make an issue stale. Requires
+    /// [`FeatureGates::collect_library_issues`].
+    pub(crate) fn is_referenced_in_tree(&self, number: u64) -> bool {
+        !self.for_issue(number).is_empty() || self.library_issues.contains(&number)
+    }
+}
+
+/// Numbers in `issue = "123"` attribute arguments. `issue = "none"` is skipped.
+fn issue_attribute_values(source: &str) -> impl Iterator<Item = u64> + '_ {
+    source.match_indices("issue").filter_map(|(i, _)| {
+        let value = source[i + "issue".len()..].trim_start().strip_prefix('=')?;
+        let value = value.trim_start().strip_prefix('"')?;
+        value[..value.find('"')?].parse().ok()
+    })
+}
+
+/// Parses the feature tuples in a `declare_features!` source file into feature gates and their
+/// tracking issue numbers.
+fn parse_features(source: &str) -> EResult<Vec<(FeatureGate, Option<u64>)>> {
+    let mut features = vec![];
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let line = strip_comment(line).trim();
+        // Feature tuples start with the status keyword, which tells them apart from other
+        // parenthesized lines.
+        let Some(rest) = line.strip_prefix('(') else {
+            continue;
+        };
+        let Some(status) =
+            rest.split(',').next().and_then(|s| FeatureStatus::from_keyword(s.trim()))
+        else {
+            continue;
+        };
+
+        // Removed features spread their reason over several lines.
+        let mut tuple = line.to_owned();
+        while !is_balanced(&tuple) {
+            let Some(line) = lines.next() else {
+                return Err(eyre!("unterminated feature declaration `{line}`"));
+            };
+            tuple.push(' ');
+            tuple.push_str(strip_comment(line).trim());
+        }
+
+        let fields = split_fields(&tuple);
+        let [_, name, since, issue, ..] = fields.as_slice() else {
+            return Err(eyre!("unexpected feature declaration `{tuple}`"));
+        };
+        let issue = match *issue {
+            "None" => None,
+            issue => Some(
+                issue
+                    .strip_prefix("Some(")
+                    .and_then(|issue| issue.strip_suffix(')'))
+                    .and_then(|issue| issue.parse().ok())
+                    .ok_or_else(|| eyre!("unexpected tracking issue in `{tuple}`"))?,
+            ),
+        };
+        features.push((
+            FeatureGate {
+                name: name.to_string(),
+                status,
+                since: since.trim_matches('"').to_owned(),
+            },
+            issue,
+        ));
+    }
+    Ok(features)
+}
+
+/// `line` without a trailing `//` comment.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut prev = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '/' if !in_string && prev == Some('/') => return &line[..i - 1],
+            _ => {}
+        }
+        prev = Some(c);
+    }
+    line
+}
+
+/// Whether all parentheses opened in `tuple` outside of string literals are closed again.
+fn is_balanced(tuple: &str) -> bool {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in tuple.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+/// Splits a `(a, b, Some(c), ...)` tuple into its top-level fields.
+fn split_fields(tuple: &str) -> Vec<&str> {
+    let inner = tuple.trim().trim_end_matches(',').trim_end();
+    let inner = inner.strip_prefix('(').and_then(|inner| inner.strip_suffix(')')).unwrap_or(inner);
+
+    let mut fields = vec![];
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                fields.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(inner[start..].trim());
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEATURES: &str = r#"
+        declare_features! (
+            /// Allows something.
+            (unstable, still_unstable, "1.45.0", Some(1)),
+            (accepted, stabilized, "1.50.0", Some(2)),
+            (removed, gone, "1.42.0", Some(3),
+             Some("merged into `#![feature(stabilized)]`"), 1234),
+            (accepted, partially_stabilized, "1.50.0", Some(4)),
+            (incomplete, partially_unstable, "1.51.0", Some(4)),
+            (accepted, stabilized_in_compiler, "1.50.0", Some(5)),
+        );
+    "#;
+
+    fn feature_gates() -> FeatureGates {
+        let mut gates = FeatureGates::default();
+        for (gate, issue) in parse_features(FEATURES).unwrap() {
+            gates.by_issue.entry(issue.unwrap()).or_default().push(gate);
+        }
+        gates.library_issues.extend(issue_attribute_values(
+            r#"#[unstable(feature = "stabilized_in_compiler", issue = "5")]
+            #[unstable(feature = "library_only", issue = "6")]
+            #[unstable(feature = "untracked", issue = "none")]"#,
+        ));
+        gates
+    }
+
+    #[test]
+    fn parses_feature_declarations() {
+        let gates = feature_gates();
+        let gone = &gates.for_issue(3)[0];
+        assert_eq!(gone.name, "gone");
+        assert_eq!(gone.status, FeatureStatus::Removed);
+        assert_eq!(gone.describe_status(), "removed in 1.42.0");
+        assert_eq!(gates.for_issue(4).len(), 2);
+        assert!(gates.library_issues.contains(&6));
+    }
+
+    #[test]
+    fn issues_with_only_accepted_or_removed_features_are_closing_candidates() {
+        let gates = feature_gates();
+        assert_eq!(gates.closing_reason(1), None);
+        assert_eq!(
+            gates.closing_reason(2).unwrap(),
+            "All features are accepted or removed: `stabilized` accepted in 1.50.0"
+        );
+        assert!(gates.closing_reason(3).is_some());
+        assert_eq!(gates.closing_reason(4), None);
+        // Still unstable in the library.
+        assert_eq!(gates.closing_reason(5), None);
+    }
+
+    #[test]
+    fn unreferenced_issues_are_not_closing_candidates() {
+        let gates = feature_gates();
+        assert_eq!(gates.closing_reason(6), None);
+        assert!(gates.is_referenced_in_tree(6));
+        assert_eq!(gates.closing_reason(7), None);
+        assert!(!gates.is_referenced_in_tree(7));
+    }
+}
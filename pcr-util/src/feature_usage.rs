@@ -0,0 +1,259 @@
+//! In-tree usages of feature gates in the `rust-lang/rust` checkout, collected on request with
+//! `--feature-usage`, to tell dead features apart from load-bearing ones.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::Serialize;
+use tracing::*;
+
+/// Directories not worth walking, they contain no Rust code using feature gates.
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "llvm-project", "gcc"];
+
+/// Where a feature gate is enabled with `#![feature(...)]`, possibly within `#![cfg_attr(...)]`, and
+/// how many UI tests mention it.
+#[derive(Default, Serialize)]
+pub(crate) struct FeatureUsage {
+    /// Crates under `compiler/`.
+    pub(crate) compiler: BTreeSet<String>,
+    /// Crates under `library/`.
+    pub(crate) library: BTreeSet<String>,
+    /// Tools under `src/`, e.g. `clippy` or `librustdoc`.
+    pub(crate) tools: BTreeSet<String>,
+    /// Test files under `tests/`.
+    pub(crate) tests: usize,
+    /// Test files under `tests/ui/` mentioning the feature gate in any way.
+    pub(crate) ui_tests: usize,
+}
+
+impl FeatureUsage {
+    /// E.g. "used by `rustc_middle` (compiler), enabled in 3 tests and mentioned in 5 UI tests".
+    pub(crate) fn describe(&self) -> String {
+        let crates =
+            [("compiler", &self.compiler), ("library", &self.library), ("tools", &self.tools)]
+                .into_iter()
+                .flat_map(|(area, crates)| {
+                    crates.iter().map(move |krate| format!("`{krate}` ({area})"))
+                })
+                .collect::<Vec<_>>();
+        let crates = match crates.as_slice() {
+            [] => "used by no crate".to_owned(),
+            crates => format!("used by {}", crates.join(", ")),
+        };
+        format!(
+            "{crates}, enabled in {} tests and mentioned in {} UI tests",
+            self.tests, self.ui_tests
+        )
+    }
+}
+
+/// Scans the checkout for usages of the `features`.
+pub(crate) fn scan(
+    repo_path: &Utf8Path,
+    features: &BTreeSet<&str>,
+) -> EResult<BTreeMap<String, FeatureUsage>> {
+    let mut usage = features
+        .iter()
+        .map(|&feature| (feature.to_owned(), FeatureUsage::default()))
+        .collect::<BTreeMap<_, _>>();
+    for area in ["compiler", "library", "src", "tests"] {
+        let dir = repo_path.join(area);
+        info!("Scanning `{dir}` for feature gate usages");
+        walk_rust_files(&dir, &mut |path, source| {
+            let relative = path.strip_prefix(repo_path).unwrap_or(path);
+            record_usage(relative, source, &mut usage);
+        })
+        .wrap_err_with(|| format!("failed to scan `{dir}` for feature gate usages"))?;
+    }
+    Ok(usage)
+}
+
+fn record_usage(path: &Utf8Path, source: &str, usage: &mut BTreeMap<String, FeatureUsage>) {
+    let components = path.components().map(|c| c.as_str()).collect::<Vec<_>>();
+    for feature in enabled_features(source) {
+        let Some(usage) = usage.get_mut(feature) else {
+            continue;
+        };
+        match components.as_slice() {
+            ["compiler", krate, ..] => _ = usage.compiler.insert(krate.to_string()),
+            ["library", krate, ..] => _ = usage.library.insert(krate.to_string()),
+            ["src", "tools", tool, ..] | ["src", tool, ..] => {
+                _ = usage.tools.insert(tool.to_string())
+            }
+            ["tests", ..] => usage.tests += 1,
+            _ => {}
+        }
+    }
+
+    if let ["tests", "ui", ..] = components.as_slice() {
+        let identifiers = source
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|identifier| !identifier.is_empty())
+            .collect::<BTreeSet<_>>();
+        for (feature, usage) in usage.iter_mut() {
+            if identifiers.contains(feature.as_str()) {
+                usage.ui_tests += 1;
+            }
+        }
+    }
+}
+
+/// Feature names enabled by the inner attributes of `source`, i.e. `#![feature(...)]` and
+/// `#![cfg_attr(<predicate>, feature(...))]`, e.g. enabling a feature only while bootstrapping.
+fn enabled_features(source: &str) -> BTreeSet<&str> {
+    let mut features = BTreeSet::new();
+    for (i, start) in source.match_indices("#![") {
+        // Leave out attributes in doc comments and string literals, e.g. in doc examples.
+        let line_start = source[..i].rfind('\n').map_or(0, |newline| newline + 1);
+        if !source[line_start..i].trim().is_empty() {
+            continue;
+        }
+        let tokens = attribute_tokens(&source[i + start.len()..]);
+        if !matches!(tokens.first(), Some(&("feature" | "cfg_attr"))) {
+            continue;
+        }
+        let mut depth = 0;
+        // The depth of the arguments of the `feature(...)` at hand, if any.
+        let mut feature_depth = None;
+        for (j, &token) in tokens.iter().enumerate() {
+            match token {
+                "(" => {
+                    depth += 1;
+                    if j > 0 && tokens[j - 1] == "feature" {
+                        feature_depth = Some(depth);
+                    }
+                }
+                ")" => {
+                    if feature_depth == Some(depth) {
+                        feature_depth = None;
+                    }
+                    depth -= 1;
+                }
+                _ if feature_depth == Some(depth) && token.starts_with(is_ident_char) => {
+                    features.insert(token);
+                }
+                _ => {}
+            }
+        }
+    }
+    features
+}
+
+/// The identifiers, string literals and punctuation of an attribute, up to its closing `]`.
+fn attribute_tokens(attr: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    for line in attr.lines() {
+        // Features are sometimes commented, e.g. with why they are needed.
+        let line = line.split("//").next().unwrap_or_default();
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let end = match c {
+                ']' => return tokens,
+                '"' => chars.find(|&(_, c)| c == '"').map_or(line.len(), |(i, _)| i + 1),
+                c if is_ident_char(c) => {
+                    while chars.next_if(|&(_, c)| is_ident_char(c)).is_some() {}
+                    chars.peek().map_or(line.len(), |&(i, _)| i)
+                }
+                c if c.is_whitespace() => continue,
+                c => start + c.len_utf8(),
+            };
+            tokens.push(&line[start..end]);
+        }
+    }
+    tokens
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Calls `f` with the path and contents of each Rust file under `dir`, if it exists.
+pub(crate) fn walk_rust_files(dir: &Utf8Path, f: &mut dyn FnMut(&Utf8Path, &str)) -> EResult<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in dir.read_dir_utf8()? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if !SKIPPED_DIRS.contains(&entry.file_name()) {
+                walk_rust_files(path, f)?;
+            }
+        } else if path.extension() == Some("rs") {
+            // Some tests are deliberately not valid UTF-8.
+            let source = fs::read(path).wrap_err_with(|| format!("failed to read `{path}`"))?;
+            f(path, &String::from_utf8_lossy(&source));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_enabled_features() {
+        let source = "\
+//! Docs mentioning `#![feature(in_docs)]`.
+//!
+//! ```
+//! #![feature(in_doc_example)]
+//! ```
+
+#![feature(box_patterns, let_chains)]
+#![feature(
+    // Needed for the `Try` impls (see below).
+    try_trait_v2,
+    never_type, // comment, with a comma
+)]
+#![cfg_attr(bootstrap, feature(gen_blocks))]
+#![cfg_attr(not(feature = \"std\"), allow(unused), feature(core_intrinsics, f16))]
+#![allow(internal_features)]
+#![doc = \"feature(in_doc_attribute)\"]
+";
+        assert_eq!(
+            enabled_features(source).into_iter().collect::<Vec<_>>(),
+            [
+                "box_patterns",
+                "core_intrinsics",
+                "f16",
+                "gen_blocks",
+                "let_chains",
+                "never_type",
+                "try_trait_v2"
+            ]
+        );
+    }
+
+    #[test]
+    fn records_usage_by_location() {
+        let mut usage = [("gen_blocks".to_owned(), FeatureUsage::default())].into_iter().collect();
+        let enabled = "#![feature(gen_blocks)]";
+        for path in [
+            "compiler/rustc_middle/src/lib.rs",
+            "library/core/src/lib.rs",
+            "src/tools/clippy/clippy_lints/src/lib.rs",
+            "src/librustdoc/lib.rs",
+            "tests/ui/coroutine/gen_block.rs",
+            "tests/codegen/gen_block.rs",
+        ] {
+            record_usage(Utf8Path::new(path), enabled, &mut usage);
+        }
+        record_usage(
+            Utf8Path::new("tests/ui/feature-gate-gen_blocks.rs"),
+            "gen_blocks {}",
+            &mut usage,
+        );
+        record_usage(Utf8Path::new("tests/run-make/gen_blocks.rs"), "gen_blocks {}", &mut usage);
+
+        let usage = &usage["gen_blocks"];
+        assert_eq!(usage.compiler.iter().collect::<Vec<_>>(), ["rustc_middle"]);
+        assert_eq!(usage.library.iter().collect::<Vec<_>>(), ["core"]);
+        assert_eq!(usage.tools.iter().collect::<Vec<_>>(), ["clippy", "librustdoc"]);
+        assert_eq!((usage.tests, usage.ui_tests), (2, 2));
+    }
+}
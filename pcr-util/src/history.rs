@@ -0,0 +1,211 @@
+//! Local SQLite history of every collected issue set, keyed by triage kind and run time.
+//!
+//! Unlike `--persist-path`, which only holds the latest snapshot, the history allows asking e.g.
+//! how long an issue has been P-high. Labels and assignees are stored as JSON arrays, so they can
+//! be queried with SQLite's JSON functions.
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use rusqlite::{Connection, OpenFlags, params};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::*;
+
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        triage_kind TEXT NOT NULL,
+        collected_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS runs_by_triage_kind ON runs (triage_kind, collected_at);
+
+    CREATE TABLE IF NOT EXISTS issues (
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        number INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        author TEXT NOT NULL,
+        url TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        labels TEXT NOT NULL,
+        assignees TEXT NOT NULL,
+        PRIMARY KEY (run_id, number)
+    );
+    CREATE INDEX IF NOT EXISTS issues_by_number ON issues (number);
+";
+
+pub(crate) struct History {
+    conn: Connection,
+}
+
+/// A recorded triage run.
+pub(crate) struct Run {
+    pub(crate) id: i64,
+    pub(crate) collected_at: OffsetDateTime,
+}
+
+impl History {
+    pub(crate) fn open(path: &Utf8Path) -> EResult<Self> {
+        let conn = Connection::open(path)
+            .wrap_err_with(|| format!("failed to open history database `{path}`"))?;
+        conn.execute_batch(SCHEMA).wrap_err("failed to create history database schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Open an existing history database for reading, which unlike [`History::open`] doesn't
+    /// create it, so a mistyped path is an error rather than an empty history.
+    pub(crate) fn open_read_only(path: &Utf8Path) -> EResult<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .wrap_err_with(|| format!("failed to open history database `{path}`"))?;
+        Ok(Self { conn })
+    }
+
+    pub(crate) fn record_run(
+        &mut self,
+        triage_kind: &str,
+        collected_at: OffsetDateTime,
+        issues: &[TriageItem],
+    ) -> EResult<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (triage_kind, collected_at) VALUES (?1, ?2)",
+            params![triage_kind, collected_at.format(&Rfc3339)?],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO issues
+                    (run_id, number, title, author, url, created_at, updated_at, labels, assignees)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for issue in issues {
+                insert.execute(params![
+                    run_id,
+                    i64::try_from(issue.number)?,
+                    issue.title,
+                    issue.author,
+                    issue.url,
+                    issue.created_at.format(&Rfc3339)?,
+                    issue.updated_at.format(&Rfc3339)?,
+                    serde_json::to_string(&issue.labels)?,
+                    serde_json::to_string(&issue.assignees)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Runs of `triage_kind`, oldest to newest.
+    pub(crate) fn runs(&self, triage_kind: &str) -> EResult<Vec<Run>> {
+        let mut select =
+            self.conn.prepare("SELECT id, collected_at FROM runs WHERE triage_kind = ?1")?;
+        let mut runs = select
+            .query_map([triage_kind], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?
+            .map(|row| {
+                let (id, collected_at) = row?;
+                Ok(Run { id, collected_at: OffsetDateTime::parse(&collected_at, &Rfc3339)? })
+            })
+            .collect::<EResult<Vec<_>>>()?;
+        // RFC 3339 timestamps with varying sub-second precision don't sort correctly as text.
+        runs.sort_by_key(|run| run.collected_at);
+        Ok(runs)
+    }
+
+    /// Issues collected in run `run_id`, ordered by issue number.
+    pub(crate) fn issues(&self, run_id: i64) -> EResult<Vec<TriageItem>> {
+        let mut select = self.conn.prepare(
+            "SELECT number, title, author, url, created_at, updated_at, labels, assignees
+            FROM issues WHERE run_id = ?1 ORDER BY number",
+        )?;
+        let rows = select.query_map([run_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (number, title, author, url, created_at, updated_at, labels, assignees) = row?;
+            Ok(TriageItem {
+                assignees: serde_json::from_str(&assignees)?,
+                author,
+                created_at: OffsetDateTime::parse(&created_at, &Rfc3339)?,
+                labels: serde_json::from_str(&labels)?,
+                number: u64::try_from(number)?,
+                title,
+                updated_at: OffsetDateTime::parse(&updated_at, &Rfc3339)?,
+                url,
+                activity: None,
+                linked_prs: None,
+                status: None,
+                pull_request: None,
+            })
+        })
+        .collect()
+    }
+}
+
+/// Record a triage run in the history database, if the triage config asks for it.
+pub(crate) fn record_if_requested(
+    config: &CommonTriageConfig,
+    triage_kind: &str,
+    collected_at: OffsetDateTime,
+    issues: &[TriageItem],
+) -> EResult<()> {
+    let Some(path) = &config.history_db else {
+        return Ok(());
+    };
+    info!("Recording {} issues of `{triage_kind}` in history database `{path}`", issues.len());
+    History::open(path)?
+        .record_run(triage_kind, collected_at, issues)
+        .wrap_err_with(|| format!("failed to record triage run in `{path}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, TestDir};
+    use crate::triage_item;
+
+    #[test]
+    fn reads_recorded_runs() {
+        let dir = TestDir::new("history-reads-recorded-runs");
+        let path = dir.path().join("history.db");
+        let issues = triage_item::simplify_repr(vec![
+            test_util::issue(1, &["P-high"], &[]),
+            test_util::issue(2, &["P-high", "T-compiler"], &["someone"]),
+        ]);
+        let collected_at = OffsetDateTime::parse("2025-01-02T03:04:05Z", &Rfc3339).unwrap();
+        History::open(&path).unwrap().record_run("p-high", collected_at, &issues).unwrap();
+
+        let history = History::open_read_only(&path).unwrap();
+        let runs = history.runs("p-high").unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].collected_at, collected_at);
+        let recorded = history.issues(runs[0].id).unwrap();
+        assert_eq!(recorded.iter().map(|issue| issue.number).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(recorded[1].assignees, ["someone"]);
+        assert!(history.runs("regression").unwrap().is_empty());
+    }
+
+    #[test]
+    fn opening_missing_database_read_only_fails() {
+        let dir = TestDir::new("history-missing-database");
+        let path = dir.path().join("mistyped.db");
+        assert!(History::open_read_only(&path).is_err());
+        assert!(!path.exists());
+    }
+}
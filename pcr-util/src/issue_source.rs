@@ -0,0 +1,236 @@
+//! Where triage issues come from: usually GitHub via the `gh` cli, but issues can also be loaded
+//! from a recorded `gh` JSON response or provided directly so triages can run offline.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::Command;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use color_eyre::Result as EResult;
+use color_eyre::eyre::{Context, eyre};
+use serde::de::DeserializeOwned;
+use tracing::*;
+
+use crate::config::{CommonConfig, CommonTriageConfig};
+use crate::query::{IssueQuery, QueryKind};
+use crate::triage_item::TriageItemRepr;
+
+/// JSON fields requested from `gh`, which must match [`TriageItemRepr`].
+const JSON_FIELDS: &str = "assignees,author,createdAt,labels,number,title,updatedAt,url";
+/// JSON fields additionally requested for pull requests.
+const PR_JSON_FIELDS: &str = "isDraft,reviewDecision,reviewRequests,mergeable,headRefName";
+
+/// GitHub search API returns at most this many results, no matter how we paginate.
+const GITHUB_SEARCH_RESULTS_CAP: usize = 1000;
+
+pub(crate) trait IssueSource {
+    fn fetch(&self, query: &IssueQuery) -> EResult<Vec<TriageItemRepr>>;
+}
+
+/// Pick an issue source based on the triage config.
+pub(crate) fn from_config(
+    config: &CommonConfig,
+    triage_config: &CommonTriageConfig,
+) -> Box<dyn IssueSource> {
+    match &triage_config.from_recording {
+        Some(path) => Box::new(RecordedSource::new(path.clone())),
+        None => Box::new(GhCliSource::new(config.clone(), triage_config.limit)),
+    }
+}
+
+/// Queries GitHub via `gh issue list` or `gh pr list`, using a `rust-lang/rust` checkout as
+/// working directory. The checkout is only needed once fetching, e.g. not when re-rendering a
+/// snapshot.
+///
+/// `gh` takes care of pagination, but stops at the limit. Since a silently truncated issue list is
+/// worse than no list at all, exceeding the limit is an error.
+pub(crate) struct GhCliSource {
+    config: CommonConfig,
+    /// Overrides the query's default limit.
+    limit: Option<u32>,
+}
+
+impl GhCliSource {
+    pub(crate) fn new(config: CommonConfig, limit: Option<u32>) -> Self {
+        Self { config, limit }
+    }
+
+    fn gh_list(&self, query: &IssueQuery, limit: u32) -> EResult<Vec<u8>> {
+        let mut cmd = Command::new("gh");
+        cmd.current_dir(self.config.checkout()?);
+        cmd.args([query.kind.gh_command(), "list"]);
+        debug!("search query: {}", query.search());
+        cmd.args(["--search", &query.search()]);
+        if !query.open_only {
+            cmd.args(["--state", "all"]);
+        }
+        cmd.args(["--limit", &limit.to_string()]);
+        match query.kind {
+            QueryKind::Issues => cmd.args(["--json", JSON_FIELDS]),
+            QueryKind::PullRequests => {
+                cmd.args(["--json", &format!("{JSON_FIELDS},{PR_JSON_FIELDS}")])
+            }
+        };
+        let res = cmd.output().wrap_err("failed to obtain JSON response via `gh` cli")?;
+        if !res.status.success() {
+            return Err(eyre!("`gh` cli command failed: {}", String::from_utf8_lossy(&res.stderr)));
+        }
+        Ok(res.stdout)
+    }
+}
+
+impl IssueSource for GhCliSource {
+    fn fetch(&self, query: &IssueQuery) -> EResult<Vec<TriageItemRepr>> {
+        let limit = self.limit.unwrap_or(query.limit);
+        info!("Downloading {} via `gh` cli (limit: {limit})", query.description);
+        // Ask for one more than the limit to tell exactly `limit` results from truncated ones.
+        let res = self.gh_list(query, limit.saturating_add(1))?;
+        let issues: Vec<TriageItemRepr> = serde_json::from_slice(&res)
+            .wrap_err("failed to deserialize JSON response as triage items")?;
+
+        if issues.len() > limit as usize {
+            return Err(eyre!(
+                "collected more than {limit} {}: the results are truncated, retry with a higher \
+                `--limit`",
+                query.description
+            ));
+        }
+        if issues.len() >= GITHUB_SEARCH_RESULTS_CAP {
+            warn!(
+                "collected {} {}, but GitHub search only returns up to {GITHUB_SEARCH_RESULTS_CAP} \
+                results: the results are likely truncated",
+                issues.len(),
+                query.description
+            );
+        }
+        Ok(issues)
+    }
+}
+
+/// Runs a GraphQL query via `gh api graphql` and returns the part of the response selected by the
+/// `jq` filter. `$owner` and `$repo` are filled in from the checkout's GitHub remote, `variables`
+/// provide the remaining query variables.
+pub(crate) fn gh_api_graphql(
+    repo_path: &Utf8Path,
+    query: &str,
+    variables: &[(&str, String)],
+    jq: &str,
+) -> EResult<Vec<u8>> {
+    let mut cmd = Command::new("gh");
+    cmd.current_dir(repo_path);
+    cmd.args(["api", "graphql"]);
+    cmd.args(["-f", &format!("query={query}")]);
+    cmd.args(["-F", "owner={owner}", "-F", "repo={repo}"]);
+    for (name, value) in variables {
+        cmd.args(["-F", &format!("{name}={value}")]);
+    }
+    cmd.args(["--jq", jq]);
+    let res = cmd.output().wrap_err("failed to obtain JSON response via `gh` cli")?;
+    if !res.status.success() {
+        return Err(eyre!("`gh` cli command failed: {}", String::from_utf8_lossy(&res.stderr)));
+    }
+    Ok(res.stdout)
+}
+
+/// Issues per batched GraphQL query. GitHub limits how many nodes a query may request, and each
+/// issue requests up to a hundred, e.g. comments or timeline items.
+const GRAPHQL_BATCH_SIZE: usize = 50;
+
+/// Runs the GraphQL field `selection(number)` selects, e.g. `issue(number: 123) { title }`, for
+/// each of `numbers` via `gh api graphql`, batching many issues per query, and returns the selected
+/// field of each issue. `fragments` defines the fragments the selections use.
+pub(crate) fn gh_api_graphql_batched<T: DeserializeOwned>(
+    repo_path: &Utf8Path,
+    numbers: &[u64],
+    selection: impl Fn(u64) -> String,
+    fragments: &str,
+) -> EResult<BTreeMap<u64, T>> {
+    let mut results = BTreeMap::new();
+    for batch in numbers.chunks(GRAPHQL_BATCH_SIZE) {
+        debug!("Querying a batch of {} issues", batch.len());
+        let query = batched_query(batch, &selection, fragments);
+        let res = gh_api_graphql(repo_path, &query, &[], ".data.repository")?;
+        // Fields are aliased `i<number>` since a query may select a field only once.
+        let mut fields: BTreeMap<String, Option<T>> =
+            serde_json::from_slice(&res).wrap_err("failed to deserialize batched JSON response")?;
+        for &number in batch {
+            let field = fields
+                .remove(&format!("i{number}"))
+                .flatten()
+                .ok_or_else(|| eyre!("no response for #{number}"))?;
+            results.insert(number, field);
+        }
+    }
+    Ok(results)
+}
+
+fn batched_query(numbers: &[u64], selection: impl Fn(u64) -> String, fragments: &str) -> String {
+    let fields = numbers
+        .iter()
+        .map(|&number| format!("    i{number}: {}\n", selection(number)))
+        .collect::<String>();
+    format!(
+        "query($owner: String!, $repo: String!) {{\n  repository(owner: $owner, name: $repo) \
+        {{\n{fields}  }}\n}}\n{fragments}"
+    )
+}
+
+/// Reads a previously recorded `gh issue list --json` or `gh pr list --json` response. The query is
+/// not applied, the recording is assumed to be the response to it.
+pub(crate) struct RecordedSource {
+    path: Utf8PathBuf,
+}
+
+impl RecordedSource {
+    pub(crate) fn new(path: Utf8PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl IssueSource for RecordedSource {
+    fn fetch(&self, query: &IssueQuery) -> EResult<Vec<TriageItemRepr>> {
+        info!("Loading {} from recording `{}`", query.description, self.path);
+        let recording = fs::read(&self.path)
+            .wrap_err_with(|| format!("failed to read recording `{}`", self.path))?;
+        serde_json::from_slice(&recording)
+            .wrap_err_with(|| format!("failed to deserialize recording `{}`", self.path))
+    }
+}
+
+/// A fixed set of issues, for testing triages without GitHub access. Like [`RecordedSource`], the
+/// query is not applied.
+#[cfg(test)]
+pub(crate) struct InMemorySource {
+    issues: Vec<TriageItemRepr>,
+}
+
+#[cfg(test)]
+impl InMemorySource {
+    pub(crate) fn new(issues: Vec<TriageItemRepr>) -> Self {
+        Self { issues }
+    }
+}
+
+#[cfg(test)]
+impl IssueSource for InMemorySource {
+    fn fetch(&self, query: &IssueQuery) -> EResult<Vec<TriageItemRepr>> {
+        debug!("Using {} in-memory {}", self.issues.len(), query.description);
+        Ok(self.issues.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batched_query_aliases_each_issue() {
+        let query =
+            batched_query(&[1, 23], |number| format!("issue(number: {number}) {{ title }}"), "");
+        assert_eq!(
+            query,
+            "query($owner: String!, $repo: String!) {\n  repository(owner: $owner, name: $repo) {\n    \
+            i1: issue(number: 1) { title }\n    i23: issue(number: 23) { title }\n  }\n}\n"
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Pull requests linked to each issue, collected on request with `--linked-prs`.
+//!
+//! A pull request is linked if it cross-references the issue, i.e. mentions it, or is manually
+//! connected to it in the GitHub UI.
+
+use std::collections::BTreeMap;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::*;
+
+use crate::config::{CommonConfig, CommonTriageConfig};
+use crate::triage_item::Author;
+use crate::{TriageItem, issue_source};
+
+const PR_FRAGMENT: &str = "
+fragment pr on PullRequest {
+  number title state isDraft author { login } updatedAt url
+}";
+
+/// Selects a page of the linked items of issue `number`, the first one or the one after `cursor`.
+fn timeline_selection(number: u64, cursor: Option<&str>) -> String {
+    let after = cursor
+        .map(|cursor| format!(", after: {}", serde_json::Value::from(cursor)))
+        .unwrap_or_default();
+    format!(
+        "issue(number: {number}) {{ \
+        timelineItems(itemTypes: [CROSS_REFERENCED_EVENT, CONNECTED_EVENT], first: 100{after}) {{ \
+        pageInfo {{ hasNextPage endCursor }} \
+        nodes {{ \
+        ... on CrossReferencedEvent {{ willCloseTarget source {{ __typename ...pr }} }} \
+        ... on ConnectedEvent {{ subject {{ __typename ...pr }} }} }} }} }}"
+    )
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkedPr {
+    pub number: u64,
+    pub title: String,
+    pub state: PrState,
+    pub is_draft: bool,
+    pub author: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub url: String,
+    /// Whether merging the pull request is supposed to close the issue, e.g. it says "Fixes #123".
+    pub closes_issue: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PrState {
+    Open,
+    Closed,
+    Merged,
+}
+
+impl PrState {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            PrState::Open => "open",
+            PrState::Closed => "closed",
+            PrState::Merged => "merged",
+        }
+    }
+}
+
+/// Whether the issue is still open although a pull request that closes it was merged.
+pub(crate) fn has_merged_fix(linked_prs: &[LinkedPr]) -> bool {
+    linked_prs.iter().any(|pr| pr.state == PrState::Merged && pr.closes_issue)
+}
+
+/// Response format.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueRepr {
+    timeline_items: TimelinePageRepr,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelinePageRepr {
+    page_info: PageInfoRepr,
+    nodes: Vec<TimelineItemRepr>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfoRepr {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelineItemRepr {
+    #[serde(default)]
+    will_close_target: bool,
+    /// The cross-referencing item of a `CrossReferencedEvent`.
+    source: Option<ItemRepr>,
+    /// The manually connected item of a `ConnectedEvent`.
+    subject: Option<ItemRepr>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "__typename")]
+enum ItemRepr {
+    PullRequest(PrRepr),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrRepr {
+    number: u64,
+    title: String,
+    state: PrState,
+    is_draft: bool,
+    /// `null` for deleted accounts.
+    author: Option<Author>,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+    url: String,
+}
+
+/// Collect the pull requests linked to each issue, if the triage config asks for it.
+///
+/// Recordings only contain the `gh issue list` response, so there are no linked pull requests to
+/// collect when replaying one.
+pub(crate) fn collect_if_requested(
+    config: &CommonConfig,
+    triage_config: &CommonTriageConfig,
+    issues: &mut [TriageItem],
+) -> EResult<()> {
+    if !triage_config.linked_prs {
+        return Ok(());
+    }
+    if triage_config.from_recording.is_some() {
+        warn!("Not collecting linked pull requests, recordings don't contain them");
+        return Ok(());
+    }
+
+    let _sp = span!(Level::INFO, "Collecting linked pull requests").entered();
+    // Pull requests aren't linked to other pull requests.
+    let numbers = issues
+        .iter()
+        .filter(|issue| !issue.is_pull_request())
+        .map(|issue| issue.number)
+        .collect::<Vec<_>>();
+    info!("Downloading pull requests linked to {} issues via `gh` cli", numbers.len());
+    let mut timelines = timelines(config.checkout()?, &numbers)
+        .wrap_err("failed to collect linked pull requests")?;
+    for issue in issues {
+        issue.linked_prs = timelines.remove(&issue.number).map(linked_prs);
+    }
+    Ok(())
+}
+
+/// The linked items of each of the issues `numbers`, following the pages of long timelines.
+fn timelines(
+    repo_path: &Utf8Path,
+    numbers: &[u64],
+) -> EResult<BTreeMap<u64, Vec<TimelineItemRepr>>> {
+    let mut timelines = BTreeMap::<u64, Vec<TimelineItemRepr>>::new();
+    // The issues with pages left, and the cursor of their next page.
+    let mut cursors = numbers.iter().map(|&number| (number, None)).collect::<BTreeMap<_, _>>();
+    while !cursors.is_empty() {
+        let numbers = cursors.keys().copied().collect::<Vec<_>>();
+        let pages = issue_source::gh_api_graphql_batched::<IssueRepr>(
+            repo_path,
+            &numbers,
+            |number| timeline_selection(number, cursors[&number].as_deref()),
+            PR_FRAGMENT,
+        )?;
+        cursors.clear();
+        for (number, issue) in pages {
+            let page = issue.timeline_items;
+            timelines.entry(number).or_default().extend(page.nodes);
+            if page.page_info.has_next_page
+                && let Some(cursor) = page.page_info.end_cursor
+            {
+                cursors.insert(number, Some(cursor));
+            }
+        }
+        if !cursors.is_empty() {
+            debug!("Downloading further timeline pages of {} issues", cursors.len());
+        }
+    }
+    Ok(timelines)
+}
+
+fn linked_prs(items: Vec<TimelineItemRepr>) -> Vec<LinkedPr> {
+    // A pull request can reference the issue several times, e.g. once per force-push that edits
+    // the commit message. It closes the issue if any of the references says so.
+    let mut linked_prs = BTreeMap::<String, LinkedPr>::new();
+    for item in items {
+        // Manually connected pull requests close the issue when merged.
+        let (pr, closes_issue) = match (item.source, item.subject) {
+            (Some(ItemRepr::PullRequest(pr)), _) => (pr, item.will_close_target),
+            (_, Some(ItemRepr::PullRequest(pr))) => (pr, true),
+            _ => continue,
+        };
+        linked_prs
+            .entry(pr.url.clone())
+            .or_insert_with(|| LinkedPr {
+                number: pr.number,
+                title: pr.title,
+                state: pr.state,
+                is_draft: pr.is_draft,
+                author: pr.author.map_or_else(|| "ghost".to_owned(), |author| author.login),
+                updated_at: pr.updated_at,
+                url: pr.url,
+                closes_issue: false,
+            })
+            .closes_issue |= closes_issue;
+    }
+
+    let mut linked_prs = linked_prs.into_values().collect::<Vec<_>>();
+    linked_prs.sort_by_key(|pr| pr.updated_at);
+    linked_prs
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn pr(number: u64, state: &str, updated_at: &str) -> Value {
+        json!({
+            "__typename": "PullRequest",
+            "number": number,
+            "title": format!("PR {number}"),
+            "state": state,
+            "isDraft": false,
+            "author": null,
+            "updatedAt": updated_at,
+            "url": format!("https://github.com/rust-lang/rust/pull/{number}"),
+        })
+    }
+
+    fn timeline(items: Vec<Value>) -> Vec<TimelineItemRepr> {
+        serde_json::from_value(Value::Array(items)).unwrap()
+    }
+
+    #[test]
+    fn merges_references_to_the_same_pull_request() {
+        let linked_prs = linked_prs(timeline(vec![
+            json!({ "willCloseTarget": false, "source": pr(2, "MERGED", "2024-11-09T00:00:00Z") }),
+            json!({ "willCloseTarget": false, "source": { "__typename": "Issue" } }),
+            json!({ "willCloseTarget": true, "source": pr(2, "MERGED", "2024-11-09T00:00:00Z") }),
+            json!({ "willCloseTarget": false, "source": pr(3, "OPEN", "2024-11-07T00:00:00Z") }),
+            json!({ "willCloseTarget": false, "source": pr(2, "MERGED", "2024-11-09T00:00:00Z") }),
+        ]));
+
+        let summary = linked_prs
+            .iter()
+            .map(|pr| (pr.number, pr.state, pr.closes_issue, pr.author.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [(3, PrState::Open, false, "ghost"), (2, PrState::Merged, true, "ghost")]
+        );
+        assert!(has_merged_fix(&linked_prs));
+    }
+
+    #[test]
+    fn connected_pull_requests_close_the_issue() {
+        let linked_prs = linked_prs(timeline(vec![
+            json!({ "subject": pr(4, "OPEN", "2024-11-07T00:00:00Z") }),
+            json!({ "subject": { "__typename": "Issue" } }),
+        ]));
+
+        let summary = linked_prs.iter().map(|pr| (pr.number, pr.closes_issue)).collect::<Vec<_>>();
+        assert_eq!(summary, [(4, true)]);
+        assert!(!has_merged_fix(&linked_prs));
+    }
+
+    #[test]
+    fn merged_pull_requests_only_mentioning_the_issue_are_no_fix() {
+        let linked_prs = linked_prs(timeline(vec![
+            json!({ "willCloseTarget": false, "source": pr(5, "MERGED", "2024-11-07T00:00:00Z") }),
+            json!({ "willCloseTarget": true, "source": pr(6, "CLOSED", "2024-11-08T00:00:00Z") }),
+        ]));
+
+        assert!(!has_merged_fix(&linked_prs));
+    }
+}
@@ -2,22 +2,48 @@
 
 #![allow(clippy::enum_variant_names)]
 
+mod activity;
 mod config;
-mod issue_metadata;
+mod feature_activity;
+mod feature_gates;
+mod feature_usage;
+mod issue_source;
+mod linked_prs;
 mod logging;
+mod markdown_stub;
+mod notes;
+mod snapshot;
+mod status_label;
+mod team;
+mod template;
+mod triage;
+mod triage_item;
+
+#[cfg(test)]
+mod test_util;
 
 use clap::Parser;
 use color_eyre::Result as EResult;
 use color_eyre::eyre::eyre;
 use config::CommonTriageConfig;
-use issue_metadata::IssueMetadata;
 use tracing::*;
 use tracing_subscriber::filter;
+use triage_item::TriageItem;
 
 use crate::config::{Config, LogLevel};
 
 mod compiler_tracking_issue;
+mod custom_triage;
+mod extract_notes;
+mod history;
+mod no_team_tracking_issue;
+mod nominations;
 mod p_high;
+mod prioritize;
+mod query;
+mod regression;
+mod snapshot_diff;
+mod stale_prs;
 
 fn main() -> EResult<()> {
     let reload_handle = logging::register_global();
@@ -30,25 +56,86 @@ fn main() -> EResult<()> {
     }
     debug!("using config: {:#?}", config);
 
-    if !config.common.repo_path.exists() {
-        return Err(eyre!("provided repo path `{}` does not exist!", config.common.repo_path));
+    if let Some(repo_path) = &config.common.repo_path
+        && !repo_path.exists()
+    {
+        return Err(eyre!("provided repo path `{repo_path}` does not exist!"));
     }
 
     info!("Performing triage: {}", config.cmd.triage_kind());
-    info!("\trepo_path:\t\t`{}`", config.common.repo_path);
+    if let Some(repo_path) = &config.common.repo_path {
+        info!("\trepo_path:\t\t`{repo_path}`");
+    }
 
     match config.cmd {
         config::Cmd::PHighTriage(triage_config) => {
             print_common_triage_config(&triage_config.common);
-            p_high::perform_triage(&config.common, &triage_config)?
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            p_high::perform_triage(&config.common, &triage_config, source.as_ref())?
         }
         config::Cmd::CompilerTrackingIssueTriage(triage_config) => {
             print_common_triage_config(&triage_config.common);
-            compiler_tracking_issue::perform_triage(&config.common, &triage_config)?;
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            compiler_tracking_issue::perform_triage(
+                &config.common,
+                &triage_config,
+                source.as_ref(),
+            )?;
         }
         config::Cmd::NoTeamTrackingIssueTriage(triage_config) => {
             print_common_triage_config(&triage_config.common);
-            todo!()
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            no_team_tracking_issue::perform_triage(
+                &config.common,
+                &triage_config,
+                source.as_ref(),
+            )?;
+        }
+        config::Cmd::RegressionTriage(triage_config) => {
+            print_common_triage_config(&triage_config.common);
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            regression::perform_triage(&config.common, &triage_config, source.as_ref())?;
+        }
+        config::Cmd::PrioritizeTriage(triage_config) => {
+            print_common_triage_config(&triage_config.common);
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            prioritize::perform_triage(&config.common, &triage_config, source.as_ref())?;
+        }
+        config::Cmd::NominationsTriage(triage_config) => {
+            print_common_triage_config(&triage_config.common);
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            nominations::perform_triage(&config.common, &triage_config, source.as_ref())?;
+        }
+        config::Cmd::StalePrTriage(triage_config) => {
+            print_common_triage_config(&triage_config.common);
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            stale_prs::perform_triage(&config.common, &triage_config, source.as_ref())?;
+        }
+        config::Cmd::CustomTriage(triage_config) => {
+            info!("\ttriage_kinds:\t\t`{}`", triage_config.triage_kinds);
+            info!("\ttriage_kind:\t\t{}", triage_config.triage_kind);
+            print_common_triage_config(&triage_config.common);
+            let source = issue_source::from_config(&config.common, &triage_config.common);
+            custom_triage::perform_triage(&config.common, &triage_config, source.as_ref())?;
+        }
+        config::Cmd::SnapshotDiff(diff_config) => {
+            info!("\told_snapshot:\t\t`{}`", diff_config.old_snapshot);
+            info!("\tnew_snapshot:\t\t`{}`", diff_config.new_snapshot);
+            info!("\tmarkdown_report_path:\t`{}`", diff_config.markdown_report_path);
+            snapshot_diff::perform_diff(&diff_config)?;
+        }
+        config::Cmd::PHighTrend(trend_config) => {
+            info!("\thistory_db:\t\t`{}`", trend_config.history_db);
+            info!("\tmarkdown_report_path:\t`{}`", trend_config.markdown_report_path);
+            if let Some(csv_path) = &trend_config.csv_path {
+                info!("\tcsv_path:\t\t`{}`", csv_path);
+            }
+            p_high::perform_trend_report(&config.common, &trend_config)?;
+        }
+        config::Cmd::ExtractNotes(extract_config) => {
+            info!("\tmarkdown_stub_path:\t`{}`", extract_config.markdown_stub_path);
+            info!("\tnotes_path:\t\t`{}`", extract_config.notes_path);
+            extract_notes::perform_extract(&extract_config)?;
         }
     };
 
@@ -56,7 +143,36 @@ fn main() -> EResult<()> {
 }
 
 fn print_common_triage_config(config: &CommonTriageConfig) {
-    info!("\tpersist_path:\t\t`{}`", config.persist_path);
+    if let Some(persist_path) = &config.persist_path {
+        info!("\tpersist_path:\t\t`{}`", persist_path);
+    }
     info!("\tmarkdown_stub_path:\t`{}`", config.markdown_stub_path);
     info!("\tmarkdown_stub_title:\t\"{}\"", config.markdown_stub_title);
+    if let Some(latest_comments) = config.latest_comments {
+        info!("\tlatest_comments:\t{}", latest_comments);
+    }
+    if config.linked_prs {
+        info!("\tlinked_prs:\t\ttrue");
+    }
+    if config.feature_gates {
+        info!("\tfeature_gates:\t\ttrue");
+    }
+    if config.feature_usage {
+        info!("\tfeature_usage:\t\ttrue");
+    }
+    if config.feature_git_activity {
+        info!("\tfeature_git_activity:\ttrue");
+    }
+    if let Some(history_db) = &config.history_db {
+        info!("\thistory_db:\t\t`{}`", history_db);
+    }
+    if let Some(limit) = config.limit {
+        info!("\tlimit:\t\t\t{}", limit);
+    }
+    if let Some(from_snapshot) = &config.from_snapshot {
+        info!("\tfrom_snapshot:\t\t`{}`", from_snapshot);
+    }
+    if let Some(from_recording) = &config.from_recording {
+        info!("\tfrom_recording:\t\t`{}`", from_recording);
+    }
 }
@@ -0,0 +1,620 @@
+//! Markdown stub rendering shared by all triage kinds. Triage kinds decide on the sections, while
+//! how each issue is rendered is up to the selected [`IssueLayout`] and [`IssueField`]s, unless the
+//! user supplied a template (see [`crate::template`]).
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use color_eyre::Result as EResult;
+use minijinja::Value;
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime};
+
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::feature_gates::{FeatureGate, FeatureGates};
+use crate::linked_prs::{self, PrState};
+use crate::notes::NotesStore;
+use crate::query::IssueQuery;
+use crate::status_label::StatusLabel;
+use crate::team::TeamTaxonomy;
+use crate::template::{FeatureGateContext, IssueContext, StubTemplate};
+use crate::triage_item::{PullRequestMetadata, ReviewDecision};
+
+/// Marks where triage notes for an issue start, so they can be extracted from the edited stub.
+pub(crate) const NOTES_MARKER: &str = "<!-- notes -->";
+/// Replaced by triage notes during triage.
+pub(crate) const NOTES_PLACEHOLDER: &str = "**TODO**";
+/// Starts the line of the notes where triagers propose a priority ahead of the meeting.
+pub(crate) const PROPOSED_PRIORITY: &str = "Proposed priority:";
+
+/// How to render each issue.
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[derive(clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueLayout {
+    /// A heading followed by one line per field.
+    FieldList,
+    /// A heading followed by a table of fields.
+    Table,
+    /// A single list item per issue, without room for notes, so there are none to extract.
+    Compact,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum IssueField {
+    Link,
+    CreationDate,
+    LastUpdated,
+    Labels,
+    Author,
+    WorkingGroups,
+    Assignees,
+    /// Feature gates tracked by the issue, according to the checkout.
+    FeatureGates,
+    /// In-tree usage of the feature gates.
+    FeatureUsage,
+    /// Recent git activity on code mentioning the feature gates.
+    FeatureActivity,
+    /// Whether a pull request is a draft, its review decision and whether it can be merged.
+    ReviewState,
+    /// Who was asked to review a pull request.
+    ReviewRequests,
+    /// The branch a pull request was opened from.
+    Branch,
+    /// The status label and how long the item has had it, if the triage kind collected it.
+    Status,
+}
+
+impl IssueField {
+    pub(crate) const DEFAULT: &[IssueField] = &[
+        IssueField::Link,
+        IssueField::CreationDate,
+        IssueField::Labels,
+        IssueField::Author,
+        IssueField::WorkingGroups,
+        IssueField::Assignees,
+    ];
+
+    /// Fields for tracking issues, including the optional feature gate information `config` asks
+    /// for.
+    pub(crate) fn tracking_issue(config: &CommonTriageConfig) -> Vec<IssueField> {
+        let mut fields = vec![IssueField::Link];
+        if config.feature_gates || config.feature_usage || config.feature_git_activity {
+            fields.push(IssueField::FeatureGates);
+        }
+        if config.feature_usage {
+            fields.push(IssueField::FeatureUsage);
+        }
+        if config.feature_git_activity {
+            fields.push(IssueField::FeatureActivity);
+        }
+        fields.extend([
+            IssueField::CreationDate,
+            IssueField::Labels,
+            IssueField::Author,
+            IssueField::WorkingGroups,
+            IssueField::Assignees,
+        ]);
+        fields
+    }
+
+    /// Whether rendering the field requires the checkout's feature gates.
+    pub(crate) fn needs_feature_gates(self) -> bool {
+        matches!(
+            self,
+            IssueField::FeatureGates | IssueField::FeatureUsage | IssueField::FeatureActivity
+        )
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            IssueField::Link => "Link",
+            IssueField::CreationDate => "Creation date",
+            IssueField::LastUpdated => "Last updated",
+            IssueField::Labels => "Labels",
+            IssueField::Author => "Author",
+            IssueField::WorkingGroups => "Working groups",
+            IssueField::Assignees => "Assignees",
+            IssueField::FeatureGates => "Feature gates",
+            IssueField::FeatureUsage => "Feature usage",
+            IssueField::FeatureActivity => "Feature git activity",
+            IssueField::ReviewState => "Review state",
+            IssueField::ReviewRequests => "Review requested from",
+            IssueField::Branch => "Branch",
+            IssueField::Status => "Status",
+        }
+    }
+}
+
+pub(crate) fn render_comma_sep_inline_code_item<S: AsRef<str>>(
+    buf: &mut String,
+    items: &[S],
+) -> EResult<()> {
+    match items {
+        [] => return Ok(()),
+        [single] => write!(buf, "`{}`", single.as_ref())?,
+        [first, rest @ ..] => {
+            write!(buf, "`{}`", first.as_ref())?;
+            for l in rest {
+                write!(buf, ", `{}`", l.as_ref())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) struct RenderCtxt<'c> {
+    config: &'c CommonTriageConfig,
+    taxonomy: &'c TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    layout: IssueLayout,
+    fields: &'c [IssueField],
+    template: Option<StubTemplate>,
+    notes: Option<NotesStore>,
+    feature_gates: Option<&'c FeatureGates>,
+    /// Triage kind specific remarks per issue number, shown prominently with the issue.
+    flags: BTreeMap<u64, Vec<String>>,
+    /// Whether the notes start with a [`PROPOSED_PRIORITY`] slot.
+    proposed_priority_slot: bool,
+    buf: String,
+}
+
+impl<'c> RenderCtxt<'c> {
+    pub(crate) fn new(
+        config: &'c CommonTriageConfig,
+        taxonomy: &'c TeamTaxonomy,
+        collected_at: OffsetDateTime,
+        layout: IssueLayout,
+        fields: &'c [IssueField],
+    ) -> EResult<Self> {
+        let template = config.template.as_deref().map(StubTemplate::load).transpose()?;
+        let notes = config.notes_path.as_deref().map(NotesStore::load_or_default).transpose()?;
+        Ok(Self {
+            buf: String::new(),
+            config,
+            taxonomy,
+            collected_at,
+            layout,
+            fields,
+            template,
+            notes,
+            feature_gates: None,
+            flags: BTreeMap::new(),
+            proposed_priority_slot: false,
+        })
+    }
+
+    /// Show `flag` prominently with issue `number`, e.g. why it needs attention.
+    pub(crate) fn flag_issue(&mut self, number: u64, flag: String) {
+        self.flags.entry(number).or_default().push(flag);
+    }
+
+    fn flags_for(&self, number: u64) -> &[String] {
+        self.flags.get(&number).map_or(&[], Vec::as_slice)
+    }
+
+    /// Start the notes of each issue with a [`PROPOSED_PRIORITY`] slot to fill in.
+    pub(crate) fn enable_proposed_priority_slot(&mut self) {
+        self.proposed_priority_slot = true;
+    }
+
+    /// Feature gates for [`IssueField::FeatureGates`], which is empty without them.
+    pub(crate) fn set_feature_gates(&mut self, feature_gates: &'c FeatureGates) {
+        self.feature_gates = Some(feature_gates);
+    }
+
+    fn feature_gates_for(&self, number: u64) -> &'c [FeatureGate] {
+        self.feature_gates.map_or(&[], |feature_gates| feature_gates.for_issue(number))
+    }
+
+    /// Render with the user-supplied template's macro `name` if it has one. Returns whether it did.
+    fn render_template_macro(&mut self, name: &str, args: &[Value]) -> EResult<bool> {
+        let Some(template) = &self.template else {
+            return Ok(false);
+        };
+        let Some(rendered) = template.render_macro(name, args)? else {
+            return Ok(false);
+        };
+        self.buf.push_str(&rendered);
+        Ok(true)
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.buf
+    }
+
+    pub(crate) fn render_document_header(&mut self) -> EResult<()> {
+        let title = Value::from(self.config.markdown_stub_title.as_str());
+        let collected_at = Value::from(self.collected_at.format(&Rfc3339)?);
+        if self.render_template_macro("header", &[title, collected_at])? {
+            return Ok(());
+        }
+
+        writeln!(&mut self.buf, "<!-- stubs generated with pcr-util -->\n")?;
+        writeln!(&mut self.buf, "# {}\n", self.config.markdown_stub_title)?;
+
+        writeln!(
+            &mut self.buf,
+            "*Issues snapshot collected on {}*\n\n",
+            self.collected_at.format(&Rfc3339).unwrap()
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn render_query_link(&mut self, query: &IssueQuery) -> EResult<()> {
+        writeln!(&mut self.buf, "[{} on GitHub]({})\n", query.description, query.browser_url())?;
+        Ok(())
+    }
+
+    /// Render a section with its issues. `link` should produce exactly the issues of the section,
+    /// `empty_message` is shown instead of the issues if there are none.
+    pub(crate) fn render_section(
+        &mut self,
+        heading: &str,
+        link: Option<&IssueQuery>,
+        empty_message: Option<&str>,
+        issues: &[&TriageItem],
+    ) -> EResult<()> {
+        let args = [
+            Value::from(heading),
+            link.map(|query| Value::from(query.browser_url())).unwrap_or_default(),
+            Value::from(issues.len()),
+        ];
+        if !self.render_template_macro("section", &args)? {
+            writeln!(&mut self.buf, "## {heading}\n")?;
+            if let Some(query) = link {
+                self.render_query_link(query)?;
+            }
+        }
+        if issues.is_empty()
+            && let Some(empty_message) = empty_message
+        {
+            writeln!(&mut self.buf, "**{empty_message}**")?;
+        }
+        self.render_issues(issues)?;
+        write!(&mut self.buf, "\n\n")?;
+        Ok(())
+    }
+
+    fn render_issues(&mut self, issues: &[&TriageItem]) -> EResult<()> {
+        issues.iter().try_for_each(|issue| self.render_issue(issue))?;
+        Ok(())
+    }
+
+    fn render_issue(&mut self, issue: &TriageItem) -> EResult<()> {
+        if self.template.is_some() {
+            let last_review = self.notes.as_ref().and_then(|notes| notes.latest(issue.number));
+            let mut issue = IssueContext::new(
+                issue,
+                self.working_groups(&issue.labels),
+                self.feature_gates
+                    .map(|gates| {
+                        let for_issue = gates.for_issue(issue.number).iter();
+                        for_issue.map(|gate| FeatureGateContext::new(gate, gates)).collect()
+                    })
+                    .unwrap_or_default(),
+                self.flags_for(issue.number),
+                last_review,
+                self.collected_at,
+            )?;
+            issue.proposed_priority_slot = self.proposed_priority_slot;
+            if self.render_template_macro("issue", &[Value::from_serialize(&issue)])? {
+                return Ok(());
+            }
+        }
+
+        match self.layout {
+            IssueLayout::FieldList => {
+                writeln!(&mut self.buf, "### #{}: {}", issue.number, issue.title)?;
+                for &field in self.fields {
+                    write!(&mut self.buf, "{}: ", field.name())?;
+                    self.render_field(field, issue)?;
+                    writeln!(&mut self.buf)?;
+                }
+                self.render_flags(issue.number)?;
+                self.render_linked_prs(issue)?;
+                self.render_latest_activity(issue)?;
+                self.render_notes_placeholder(issue.number)?;
+            }
+            IssueLayout::Table => {
+                writeln!(&mut self.buf, "### #{}: {}", issue.number, issue.title)?;
+                writeln!(&mut self.buf, "| Kind | Value |")?;
+                writeln!(&mut self.buf, "| - | - |")?;
+                for &field in self.fields {
+                    write!(&mut self.buf, "| {} | ", field.name())?;
+                    self.render_field(field, issue)?;
+                    writeln!(&mut self.buf, " |")?;
+                }
+                self.render_flags(issue.number)?;
+                self.render_linked_prs(issue)?;
+                self.render_latest_activity(issue)?;
+                self.render_notes_placeholder(issue.number)?;
+            }
+            IssueLayout::Compact => {
+                write!(&mut self.buf, "- [#{}]({}): {}", issue.number, issue.url, issue.title)?;
+                for &field in self.fields {
+                    // Already part of the issue reference.
+                    if matches!(field, IssueField::Link) {
+                        continue;
+                    }
+                    let field_start = self.buf.len();
+                    write!(&mut self.buf, "; {}: ", field.name().to_lowercase())?;
+                    let value_start = self.buf.len();
+                    self.render_field(field, issue)?;
+                    // Leave out empty fields to keep it compact.
+                    if self.buf.len() == value_start {
+                        self.buf.truncate(field_start);
+                    }
+                }
+                for flag in self.flags.get(&issue.number).into_iter().flatten() {
+                    write!(&mut self.buf, "; **{flag}**")?;
+                }
+                if let Some(linked_prs) = &issue.linked_prs {
+                    write!(&mut self.buf, "; {} linked PRs", linked_prs.len())?;
+                    if linked_prs::has_merged_fix(linked_prs) {
+                        write!(&mut self.buf, " (**merged fix, still open**)")?;
+                    }
+                }
+                if let Some(activity) = &issue.activity {
+                    write!(&mut self.buf, "; {} comments", activity.comment_count)?;
+                }
+                writeln!(&mut self.buf)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_flags(&mut self, number: u64) -> EResult<()> {
+        let Some(flags) = self.flags.get(&number) else {
+            return Ok(());
+        };
+        writeln!(&mut self.buf)?;
+        for flag in flags {
+            writeln!(&mut self.buf, "**{flag}**")?;
+        }
+        Ok(())
+    }
+
+    /// Pull requests linked to the issue, if they were collected.
+    fn render_linked_prs(&mut self, issue: &TriageItem) -> EResult<()> {
+        let Some(linked_prs) = &issue.linked_prs else {
+            return Ok(());
+        };
+        writeln!(&mut self.buf)?;
+        if linked_prs.is_empty() {
+            writeln!(&mut self.buf, "*No linked PRs*")?;
+            return Ok(());
+        }
+
+        if linked_prs::has_merged_fix(linked_prs) {
+            writeln!(&mut self.buf, "**Has a merged fix but is still open**\n")?;
+        }
+        writeln!(&mut self.buf, "Linked PRs:")?;
+        for pr in linked_prs {
+            write!(
+                &mut self.buf,
+                "- [#{}]({}) by `{}` ({}",
+                pr.number,
+                pr.url,
+                pr.author,
+                pr.state.name()
+            )?;
+            if pr.is_draft && pr.state == PrState::Open {
+                write!(&mut self.buf, ", draft")?;
+            }
+            if pr.closes_issue {
+                write!(&mut self.buf, ", closes issue")?;
+            }
+            writeln!(&mut self.buf, "): {}, last updated {}", pr.title, pr.updated_at.date())?;
+        }
+        Ok(())
+    }
+
+    /// Collapsed list of the latest comments, if they were collected.
+    fn render_latest_activity(&mut self, issue: &TriageItem) -> EResult<()> {
+        let Some(activity) = &issue.activity else {
+            return Ok(());
+        };
+        writeln!(&mut self.buf)?;
+        let Some(last) = activity.latest_comments.last() else {
+            writeln!(&mut self.buf, "*No comments*")?;
+            return Ok(());
+        };
+
+        writeln!(
+            &mut self.buf,
+            "<details><summary>Latest activity: {} comments, last on {} by <code>{}</code></summary>\n",
+            activity.comment_count,
+            last.created_at.date(),
+            last.author
+        )?;
+        for comment in activity.latest_comments.iter().rev() {
+            writeln!(
+                &mut self.buf,
+                "- [{}]({}) by `{}`: {}",
+                comment.created_at.date(),
+                comment.url,
+                comment.author,
+                comment_excerpt(&comment.body)
+            )?;
+        }
+        writeln!(&mut self.buf, "\n</details>")?;
+        Ok(())
+    }
+
+    /// Notes from the previous triage go before the [`NOTES_MARKER`], so they don't end up in
+    /// the notes extracted from this triage.
+    fn render_notes_placeholder(&mut self, number: u64) -> EResult<()> {
+        writeln!(&mut self.buf)?;
+        if let Some(last_review) = self.notes.as_ref().and_then(|notes| notes.latest(number)) {
+            write!(&mut self.buf, "*Last reviewed on {}", last_review.reviewed_on)?;
+            if let Some(decision) = &last_review.decision {
+                write!(&mut self.buf, " (decision: {decision})")?;
+            }
+            writeln!(&mut self.buf, ":*\n")?;
+            for line in last_review.notes.lines() {
+                writeln!(&mut self.buf, "{}", format!("> {line}").trim_end())?;
+            }
+            writeln!(&mut self.buf)?;
+        }
+        writeln!(&mut self.buf, "{NOTES_MARKER}")?;
+        if self.proposed_priority_slot {
+            writeln!(&mut self.buf, "{PROPOSED_PRIORITY} {NOTES_PLACEHOLDER}\n")?;
+        }
+        writeln!(&mut self.buf, "{NOTES_PLACEHOLDER}\n\n")?;
+        Ok(())
+    }
+
+    fn render_field(&mut self, field: IssueField, issue: &TriageItem) -> EResult<()> {
+        match field {
+            IssueField::Link => self.render_issue_link(&issue.url),
+            IssueField::CreationDate => self.render_date(issue.created_at.date()),
+            IssueField::LastUpdated => self.render_date(issue.updated_at.date()),
+            IssueField::Labels => render_comma_sep_inline_code_item(&mut self.buf, &issue.labels),
+            IssueField::Author => self.render_author(&issue.author),
+            IssueField::WorkingGroups => self.render_wg(&issue.labels),
+            IssueField::Assignees => {
+                render_comma_sep_inline_code_item(&mut self.buf, &issue.assignees)
+            }
+            IssueField::FeatureGates => self.render_feature_gates(issue.number),
+            IssueField::FeatureUsage => self.render_feature_usage(issue.number),
+            IssueField::FeatureActivity => self.render_feature_activity(issue.number),
+            IssueField::Status => self.render_status(issue.status.as_ref()),
+            IssueField::ReviewState | IssueField::ReviewRequests | IssueField::Branch => {
+                match &issue.pull_request {
+                    Some(pr) => self.render_pull_request_field(field, pr),
+                    // Issues don't have these fields.
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    fn render_issue_link(&mut self, url: &str) -> EResult<()> {
+        write!(&mut self.buf, "<{url}>")?;
+        Ok(())
+    }
+
+    fn render_date(&mut self, date: Date) -> EResult<()> {
+        write!(&mut self.buf, "{date}")?;
+        Ok(())
+    }
+
+    fn render_author(&mut self, author: &str) -> EResult<()> {
+        write!(&mut self.buf, "`{author}`")?;
+        Ok(())
+    }
+
+    fn render_feature_gates(&mut self, number: u64) -> EResult<()> {
+        for (i, gate) in self.feature_gates_for(number).iter().enumerate() {
+            if i > 0 {
+                write!(&mut self.buf, ", ")?;
+            }
+            write!(&mut self.buf, "`{}` ({})", gate.name, gate.describe_status())?;
+        }
+        Ok(())
+    }
+
+    fn render_status(&mut self, status: Option<&StatusLabel>) -> EResult<()> {
+        let Some(status) = status else {
+            return Ok(());
+        };
+        write!(&mut self.buf, "`{}`", status.label)?;
+        match (status.since, status.days(self.collected_at)) {
+            (Some(since), Some(days)) => {
+                write!(&mut self.buf, " for {days} days (since {})", since.date())?
+            }
+            _ => write!(&mut self.buf, " for an unknown time")?,
+        }
+        Ok(())
+    }
+
+    fn render_pull_request_field(
+        &mut self,
+        field: IssueField,
+        pr: &PullRequestMetadata,
+    ) -> EResult<()> {
+        match field {
+            IssueField::ReviewState => {
+                let state = pr
+                    .is_draft
+                    .then_some("draft")
+                    .into_iter()
+                    .chain(pr.review_decision.map(ReviewDecision::name))
+                    .chain([pr.mergeable.name()])
+                    .collect::<Vec<_>>();
+                write!(&mut self.buf, "{}", state.join(", "))?;
+            }
+            IssueField::ReviewRequests => {
+                render_comma_sep_inline_code_item(&mut self.buf, &pr.review_requests)?
+            }
+            IssueField::Branch => write!(&mut self.buf, "`{}`", pr.head_ref_name)?,
+            _ => unreachable!("`{field:?}` is not a pull request field"),
+        }
+        Ok(())
+    }
+
+    fn render_feature_usage(&mut self, number: u64) -> EResult<()> {
+        self.render_per_feature(number, |gates, name| Some(gates.usage(name)?.describe()))
+    }
+
+    fn render_feature_activity(&mut self, number: u64) -> EResult<()> {
+        self.render_per_feature(number, |gates, name| Some(gates.activity(name)?.describe()))
+    }
+
+    /// Renders `describe` of each feature gate tracked by issue `number`, separated by `;`.
+    fn render_per_feature(
+        &mut self,
+        number: u64,
+        describe: impl Fn(&FeatureGates, &str) -> Option<String>,
+    ) -> EResult<()> {
+        let Some(feature_gates) = self.feature_gates else {
+            return Ok(());
+        };
+        let descriptions = feature_gates
+            .for_issue(number)
+            .iter()
+            .filter_map(|gate| Some((&gate.name, describe(feature_gates, &gate.name)?)))
+            .collect::<Vec<_>>();
+        for (i, (name, description)) in descriptions.iter().enumerate() {
+            if i > 0 {
+                write!(&mut self.buf, "; ")?;
+            }
+            write!(&mut self.buf, "`{name}` {description}")?;
+        }
+        Ok(())
+    }
+
+    fn render_wg(&mut self, labels: &[String]) -> EResult<()> {
+        let working_groups = self.working_groups(labels);
+        render_comma_sep_inline_code_item(&mut self.buf, working_groups.as_slice())
+    }
+
+    fn working_groups<'l>(&self, labels: &'l [String]) -> Vec<&'l str> {
+        labels
+            .iter()
+            .map(String::as_str)
+            .filter(|label| self.taxonomy.is_working_group(label))
+            .collect()
+    }
+}
+
+/// First line of text of a comment, shortened to fit in a list item.
+fn comment_excerpt(body: &str) -> String {
+    const MAX_CHARS: usize = 100;
+
+    let line = body
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('>') && !line.starts_with("```"))
+        .unwrap_or_default();
+    if line.chars().count() <= MAX_CHARS {
+        return line.to_owned();
+    }
+    let mut excerpt = line.chars().take(MAX_CHARS).collect::<String>();
+    excerpt.push('…');
+    excerpt
+}
@@ -0,0 +1,10 @@
+use crate::query::IssueQuery;
+use crate::team::TeamTaxonomy;
+
+pub(crate) fn no_team_tracking_issue_query(taxonomy: &TeamTaxonomy) -> IssueQuery {
+    // GitHub search can't express "no team label", so exclude each team label.
+    IssueQuery::new("tracking issues without team label")
+        .sort("updated-asc")
+        .label("C-tracking-issue")
+        .without_labels(taxonomy.teams())
+}
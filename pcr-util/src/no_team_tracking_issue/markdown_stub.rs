@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::feature_gates::FeatureGates;
+use crate::markdown_stub::{IssueField, IssueLayout, RenderCtxt};
+use crate::query::IssueQuery;
+use crate::team::TeamTaxonomy;
+use crate::triage_item::label_matches;
+
+/// Label heuristics for guessing the owning team, checked in order (see [`label_matches`] for the
+/// pattern syntax). Nominations come first since they are an explicit request for a team's
+/// attention.
+const LABEL_HEURISTICS: &[(&str, &str)] = &[
+    ("I-compiler-nominated", "T-compiler"),
+    ("I-lang-nominated", "T-lang"),
+    ("I-libs-api-nominated", "T-libs-api"),
+    ("I-libs-nominated", "T-libs"),
+    ("I-types-nominated", "T-types"),
+    ("I-rustdoc-nominated", "T-rustdoc"),
+    ("Libs-Tracked", "T-libs-api"),
+    ("Libs-Small", "T-libs-api"),
+    ("A-rustdoc*", "T-rustdoc"),
+    ("A-cargo", "T-cargo"),
+    ("A-bootstrap*", "T-bootstrap"),
+    ("A-trait-system", "T-types"),
+    ("A-type-system", "T-types"),
+    ("A-inference", "T-types"),
+    ("A-diagnostics", "T-compiler"),
+    ("A-lints", "T-compiler"),
+    ("A-codegen", "T-compiler"),
+    ("A-LLVM", "T-compiler"),
+    ("A-debuginfo", "T-compiler"),
+    ("A-linkage", "T-compiler"),
+    ("A-sanitizers", "T-compiler"),
+    ("A-cli", "T-compiler"),
+    ("A-incr-comp", "T-compiler"),
+    ("F-*", "T-lang"),
+];
+
+/// Title heuristics (matched case-insensitively) for when no label gives a hint.
+const TITLE_HEURISTICS: &[(&str, &str)] =
+    &[("rustdoc", "T-rustdoc"), ("cargo", "T-cargo"), ("codegen", "T-compiler")];
+
+/// Tracking issues for compiler flags, e.g. `-Zsanitizer` or `-C instrument-coverage`, are likely
+/// T-compiler's. Matched case-sensitively and only at the start of a word.
+const COMPILER_FLAG_PREFIXES: &[&str] = &["-Z", "-C"];
+
+/// Group heading for issues where none of the heuristics matched.
+const UNKNOWN_TEAM: &str = "unknown team";
+
+pub(crate) struct ReviewInfo<'a> {
+    pub(crate) tracking_issues: &'a [TriageItem],
+}
+
+impl<'a> ReviewInfo<'a> {
+    pub(crate) fn new(tracking_issues: &'a [TriageItem]) -> Self {
+        Self { tracking_issues }
+    }
+
+    /// Group tracking issues by their most likely owning team. Issues which we can't make a guess
+    /// for are grouped last.
+    pub(crate) fn group_by_likely_team(&'a self) -> Vec<(&'static str, Vec<&'a TriageItem>)> {
+        let mut groups: BTreeMap<&'static str, Vec<&'a TriageItem>> = BTreeMap::new();
+        for issue in self.tracking_issues {
+            groups.entry(likely_team(issue).unwrap_or(UNKNOWN_TEAM)).or_default().push(issue);
+        }
+
+        let unknown = groups.remove(UNKNOWN_TEAM);
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        if let Some(unknown) = unknown {
+            groups.push((UNKNOWN_TEAM, unknown));
+        }
+        groups
+    }
+}
+
+fn likely_team(issue: &TriageItem) -> Option<&'static str> {
+    let by_label = LABEL_HEURISTICS.iter().find_map(|&(pattern, team)| {
+        issue.labels.iter().any(|label| label_matches(label, pattern)).then_some(team)
+    });
+    by_label
+        .or_else(|| {
+            let title = issue.title.to_lowercase();
+            TITLE_HEURISTICS
+                .iter()
+                .find_map(|&(needle, team)| title.contains(needle).then_some(team))
+        })
+        .or_else(|| mentions_compiler_flag(&issue.title).then_some("T-compiler"))
+}
+
+/// Whether the title mentions a flag like `-Zflag-name`, `-Z flag-name`, `-Z flag=value`. To not
+/// mistake prose like "use -C or -D" for a flag, a flag separated by a space from its prefix must
+/// contain `-` or `_`, or be followed by `=`.
+fn mentions_compiler_flag(title: &str) -> bool {
+    COMPILER_FLAG_PREFIXES.iter().any(|prefix| {
+        title.match_indices(prefix).any(|(start, _)| {
+            let at_word_start =
+                title[..start].chars().next_back().is_none_or(|c| !c.is_alphanumeric() && c != '-');
+            let rest = &title[start + prefix.len()..];
+            let (name, separated) = match rest.strip_prefix(' ') {
+                Some(rest) => (rest, true),
+                None => (rest, false),
+            };
+            let name_len = name
+                .find(|c: char| {
+                    !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+                })
+                .unwrap_or(name.len());
+            let (name, after) = name.split_at(name_len);
+            at_word_start
+                && name.starts_with(|c: char| c.is_ascii_lowercase())
+                && (!separated || name.contains(['-', '_']) || after.starts_with('='))
+        })
+    })
+}
+
+pub(crate) fn render_markdown_stub<'a>(
+    config: &CommonTriageConfig,
+    taxonomy: &TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    query: &IssueQuery,
+    info: ReviewInfo<'a>,
+    feature_gates: Option<&FeatureGates>,
+) -> EResult<String> {
+    let layout = config.issue_layout.unwrap_or(IssueLayout::Table);
+    let fields = IssueField::tracking_issue(config);
+    let mut ctx = RenderCtxt::new(config, taxonomy, collected_at, layout, &fields)?;
+    if let Some(feature_gates) = feature_gates {
+        ctx.set_feature_gates(feature_gates);
+    }
+    ctx.render_document_header()?;
+    ctx.render_query_link(query)?;
+
+    for (team, issues) in info.group_by_likely_team() {
+        let heading = format!("Tracking issues without team label (likely {team})");
+        ctx.render_section(&heading, None, None, &issues)?;
+    }
+
+    Ok(ctx.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_compiler_flags() {
+        assert!(mentions_compiler_flag("Tracking issue for `-Zsanitizer=kcfi`"));
+        assert!(mentions_compiler_flag("Tracking issue for -Z threads=N"));
+        assert!(mentions_compiler_flag("Tracking Issue for `-C instrument-coverage`"));
+        assert!(mentions_compiler_flag("-Cpanic=immediate-abort"));
+    }
+
+    #[test]
+    fn ignores_prose_resembling_compiler_flags() {
+        assert!(!mentions_compiler_flag("Tracking issue for non-zero integer types"));
+        assert!(!mentions_compiler_flag("Tracking issue for `Box::new_zeroed`"));
+        assert!(!mentions_compiler_flag("Tracking issue for `alloc-zeroed`"));
+        assert!(!mentions_compiler_flag("Tracking issue for -zeroed allocations"));
+        assert!(!mentions_compiler_flag("Tracking issue for FFI-C unwinding"));
+        assert!(!mentions_compiler_flag("Allow -C or -D on the command line"));
+        assert!(!mentions_compiler_flag("Tracking issue for -c style flags"));
+    }
+}
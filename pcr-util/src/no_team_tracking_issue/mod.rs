@@ -0,0 +1,86 @@
+mod cmd;
+mod markdown_stub;
+
+use self::markdown_stub::ReviewInfo;
+use crate::EResult;
+use crate::config::{CommonConfig, NoTeamTrackingIssueTriage};
+use crate::feature_gates::FeatureGates;
+use crate::issue_source::IssueSource;
+use crate::markdown_stub::IssueField;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "no-team-tracking-issue";
+
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &NoTeamTrackingIssueTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let taxonomy = TeamTaxonomy::load(config)?;
+    let query = cmd::no_team_tracking_issue_query(&taxonomy);
+    let spec = TriageSpec {
+        kind: TRIAGE_KIND,
+        description: "Tracking issues without team label",
+        queries: std::slice::from_ref(&query),
+    };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |_| Ok(()),
+        |collected_at, no_team_tracking_issues| {
+            let review_info = ReviewInfo::new(no_team_tracking_issues);
+            let feature_gates = FeatureGates::load_for_fields(
+                config,
+                &IssueField::tracking_issue(&triage_config.common),
+                no_team_tracking_issues,
+            )?;
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                &query,
+                review_info,
+                feature_gates.as_ref(),
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue_source::InMemorySource;
+    use crate::test_util::{self, TestDir};
+
+    #[test]
+    fn query_leaves_out_issues_with_team_label() {
+        let taxonomy = TeamTaxonomy::builtin();
+        let search = cmd::no_team_tracking_issue_query(&taxonomy).search();
+        assert!(search.contains(" label:C-tracking-issue"));
+        for team in taxonomy.teams() {
+            assert!(search.contains(&format!("-label:{team}")), "`{team}` isn't left out");
+        }
+    }
+
+    #[test]
+    fn renders_tracking_issues() {
+        let dir = TestDir::new("no-team-tracking-issue-triage");
+        let (mut config, common) = test_util::configs(&dir);
+        // Without feature gate fields, rendering doesn't need a checkout.
+        config.repo_path = None;
+        let source = InMemorySource::new(vec![
+            test_util::issue(2, &["C-tracking-issue", "A-rustdoc"], &[]),
+            test_util::issue(1, &["C-tracking-issue"], &[]),
+        ]);
+
+        perform_triage(&config, &NoTeamTrackingIssueTriage { common }, &source).unwrap();
+
+        let stub = dir.read("stub.md");
+        assert!(stub.contains("### #1: Issue 1"));
+        assert!(stub.contains("### #2: Issue 2"));
+    }
+}
@@ -0,0 +1,30 @@
+use super::{BACKPORT_CHANNELS, NOMINATION_LABEL};
+use crate::query::IssueQuery;
+
+/// Issues nominated for discussion in the compiler team meeting.
+pub(crate) fn nominated_issues_query() -> IssueQuery {
+    IssueQuery::new("nominated issues").sort("created-asc").label(NOMINATION_LABEL)
+}
+
+/// Pull requests nominated for discussion in the compiler team meeting.
+pub(crate) fn nominated_prs_query() -> IssueQuery {
+    IssueQuery::pull_requests("nominated PRs").sort("created-asc").label(NOMINATION_LABEL)
+}
+
+/// T-compiler pull requests nominated for a backport to `channel`, e.g. `beta`. These are usually
+/// merged already.
+pub(crate) fn backport_nominations_query(channel: &str) -> IssueQuery {
+    IssueQuery::pull_requests(format!("{channel} backport nominations"))
+        .any_state()
+        .sort("created-asc")
+        .label(&format!("{channel}-nominated"))
+        .label("T-compiler")
+}
+
+pub(crate) fn all_queries() -> Vec<IssueQuery> {
+    BACKPORT_CHANNELS
+        .iter()
+        .map(|channel| backport_nominations_query(channel))
+        .chain([nominated_prs_query(), nominated_issues_query()])
+        .collect()
+}
@@ -0,0 +1,100 @@
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use super::{BACKPORT_CHANNELS, NOMINATION_LABEL, cmd};
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::markdown_stub::{IssueField, IssueLayout, RenderCtxt};
+use crate::team::TeamTaxonomy;
+
+/// Like the agenda, which links each item and names who opened it.
+const FIELDS: &[IssueField] = &[IssueField::Link, IssueField::Author, IssueField::Assignees];
+
+pub(crate) struct ReviewInfo<'a> {
+    pub(crate) nominations: &'a [TriageItem],
+}
+
+impl<'a> ReviewInfo<'a> {
+    pub(crate) fn new(nominations: &'a [TriageItem]) -> Self {
+        Self { nominations }
+    }
+
+    /// T-compiler pull requests nominated for a backport to `channel`.
+    pub(crate) fn backport_nominations(&'a self, channel: &str) -> Vec<&'a TriageItem> {
+        let label = format!("{channel}-nominated");
+        self.nominations
+            .iter()
+            .filter(|item| item.is_pull_request() && has_label(item, &label))
+            .filter(|item| has_label(item, "T-compiler"))
+            .collect()
+    }
+
+    /// Issues or pull requests nominated for discussion.
+    pub(crate) fn nominated(&'a self, pull_requests: bool) -> Vec<&'a TriageItem> {
+        self.nominations
+            .iter()
+            .filter(|item| item.is_pull_request() == pull_requests)
+            .filter(|item| has_label(item, NOMINATION_LABEL))
+            .collect()
+    }
+}
+
+fn has_label(item: &TriageItem, label: &str) -> bool {
+    item.labels.iter().any(|l| l == label)
+}
+
+/// Whether the backport to `channel` was already decided on, according to the labels of `pr`.
+fn backport_decision(pr: &TriageItem, channel: &str) -> Option<String> {
+    ["accepted", "declined"]
+        .iter()
+        .map(|decision| format!("{channel}-{decision}"))
+        .find(|label| has_label(pr, label))
+}
+
+pub(crate) fn render_markdown_stub<'a>(
+    config: &CommonTriageConfig,
+    taxonomy: &TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    info: ReviewInfo<'a>,
+) -> EResult<String> {
+    // Unlike the compact layout, leaves room for meeting notes which `extract-notes` can pick up.
+    let layout = config.issue_layout.unwrap_or(IssueLayout::FieldList);
+    let mut ctx = RenderCtxt::new(config, taxonomy, collected_at, layout, FIELDS)?;
+    for &channel in BACKPORT_CHANNELS {
+        for pr in info.backport_nominations(channel) {
+            if let Some(decision) = backport_decision(pr, channel) {
+                ctx.flag_issue(pr.number, format!("Already labelled `{decision}`"));
+            }
+        }
+    }
+    ctx.render_document_header()?;
+
+    for &channel in BACKPORT_CHANNELS {
+        let nominations = info.backport_nominations(channel);
+        ctx.render_section(
+            &format!("{}-nominations", capitalize(channel)),
+            Some(&cmd::backport_nominations_query(channel)),
+            Some(&format!("No {channel} nominations for T-compiler this time")),
+            &nominations,
+        )?;
+    }
+    ctx.render_section(
+        "Nominated PRs",
+        Some(&cmd::nominated_prs_query()),
+        Some("No nominated PRs this time"),
+        &info.nominated(true),
+    )?;
+    ctx.render_section(
+        "Nominated issues",
+        Some(&cmd::nominated_issues_query()),
+        Some("No nominated issues this time"),
+        &info.nominated(false),
+    )?;
+
+    Ok(ctx.finish())
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
@@ -0,0 +1,70 @@
+mod cmd;
+mod markdown_stub;
+
+use self::markdown_stub::ReviewInfo;
+use crate::EResult;
+use crate::config::{CommonConfig, NominationsTriage};
+use crate::issue_source::IssueSource;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "nominations";
+
+/// Label nominating an issue or pull request for discussion in the compiler team meeting.
+const NOMINATION_LABEL: &str = "I-compiler-nominated";
+
+/// Release channels pull requests can be nominated for a backport to, in agenda order.
+const BACKPORT_CHANNELS: &[&str] = &["beta", "stable"];
+
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &NominationsTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let spec =
+        TriageSpec { kind: TRIAGE_KIND, description: "Nominations", queries: &cmd::all_queries() };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |_| Ok(()),
+        |collected_at, nominations| {
+            let review_info = ReviewInfo::new(nominations);
+            let taxonomy = TeamTaxonomy::load(config)?;
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                review_info,
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue_source::InMemorySource;
+    use crate::markdown_stub::{NOTES_MARKER, NOTES_PLACEHOLDER};
+    use crate::test_util::{self, TestDir};
+
+    #[test]
+    fn leaves_room_for_meeting_notes() {
+        let dir = TestDir::new("nominations-triage");
+        let (config, common) = test_util::configs(&dir);
+        // Every query returns these, they are only listed once.
+        let source = InMemorySource::new(vec![
+            test_util::issue(1, &[NOMINATION_LABEL, "T-compiler"], &[]),
+            test_util::issue(2, &[NOMINATION_LABEL, "T-compiler"], &[]),
+        ]);
+
+        perform_triage(&config, &NominationsTriage { common }, &source).unwrap();
+
+        let stub = dir.read("stub.md");
+        assert_eq!(stub.matches("### #").count(), 2);
+        assert_eq!(stub.matches(NOTES_MARKER).count(), 2);
+        assert_eq!(stub.matches(NOTES_PLACEHOLDER).count(), 2);
+    }
+}
@@ -0,0 +1,63 @@
+//! Triage notes extracted from edited Markdown stubs, kept per issue across triages.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+time::serde::format_description!(iso_date, Date, "[year]-[month]-[day]");
+
+/// What a triage concluded about an issue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct TriageNote {
+    #[serde(with = "iso_date")]
+    pub(crate) reviewed_on: Date,
+    /// Issue title at the time of the triage.
+    pub(crate) title: String,
+    /// E.g. "close", "keep" or "demote", from a `Decision: <keyword>` line in the notes.
+    pub(crate) decision: Option<String>,
+    pub(crate) notes: String,
+}
+
+/// Triage notes of each issue, oldest to newest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct NotesStore {
+    issues: BTreeMap<u64, Vec<TriageNote>>,
+}
+
+impl NotesStore {
+    /// Load the notes store at `path`, or start an empty one if it doesn't exist yet.
+    pub(crate) fn load_or_default(path: &Utf8Path) -> EResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json =
+            fs::read(path).wrap_err_with(|| format!("failed to read notes store `{path}`"))?;
+        serde_json::from_slice(&json)
+            .wrap_err_with(|| format!("failed to deserialize notes store `{path}`"))
+    }
+
+    pub(crate) fn save(&self, path: &Utf8Path) -> EResult<()> {
+        let json = serde_json::to_vec_pretty(self)?;
+        fs::write(path, &json).wrap_err_with(|| format!("failed to write notes store `{path}`"))
+    }
+
+    /// The most recent triage note of issue `number`.
+    pub(crate) fn latest(&self, number: u64) -> Option<&TriageNote> {
+        self.issues.get(&number)?.last()
+    }
+
+    /// Record a triage note, replacing an earlier note for the issue from the same day.
+    pub(crate) fn insert(&mut self, number: u64, note: TriageNote) {
+        let notes = self.issues.entry(number).or_default();
+        notes.retain(|existing| existing.reviewed_on != note.reviewed_on);
+        notes.push(note);
+        notes.sort_by_key(|note| note.reviewed_on);
+    }
+}
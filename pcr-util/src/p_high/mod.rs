@@ -1,45 +1,73 @@
 mod cmd;
 mod markdown_stub;
-
-use std::fs;
-
-use color_eyre::eyre::Context;
-use tracing::*;
+mod trend;
 
 use self::markdown_stub::ReviewInfo;
+pub(crate) use self::trend::perform_trend_report;
 use crate::EResult;
 use crate::config::{CommonConfig, PHighTriage};
-use crate::issue_metadata::{self, IssueMetadataRepr};
+use crate::issue_source::IssueSource;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
 
-pub(crate) fn perform_triage(config: &CommonConfig, triage_config: &PHighTriage) -> EResult<()> {
-    let p_high = {
-        let _sp = span!(Level::INFO, "Collecting P-high issues").entered();
-        let p_high = cmd::p_high_cmd(&config.repo_path)?;
-        let p_high: Vec<IssueMetadataRepr> = serde_json::from_slice(&p_high)
-            .wrap_err("failed to deserialize JSON response as issue metadata")?;
-        let mut p_high = issue_metadata::simplify_repr(p_high);
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "p-high";
 
-        // Intentionally sort by oldest to newest.
-        p_high.sort_by(|a, b| a.number.cmp(&b.number));
-        p_high
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &PHighTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let spec = TriageSpec {
+        kind: TRIAGE_KIND,
+        description: "P-high issues",
+        queries: &[cmd::p_high_query()],
     };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |_| Ok(()),
+        |collected_at, p_high| {
+            let taxonomy = TeamTaxonomy::load(config)?;
+            let review_info = ReviewInfo::new(p_high, &taxonomy);
+            markdown_stub::render_markdown_stub(&triage_config.common, collected_at, review_info)
+        },
+    )
+}
 
-    info!("P-high issues count: {}", p_high.len());
-    info!("Writing P-high issue metadata json to `{}`", triage_config.common.persist_path);
-    let json = serde_json::to_vec_pretty(&p_high)?;
-    fs::write(&triage_config.common.persist_path, &json).wrap_err_with(|| {
-        format!("failed to write response to `{}`", triage_config.common.persist_path)
-    })?;
-
-    let review_info = ReviewInfo::new(&p_high);
-    info!("Writing markdown stub to `{}`", triage_config.common.markdown_stub_path);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue_source::InMemorySource;
+    use crate::test_util::{self, TestDir};
 
-    let stub = markdown_stub::render_markdown_stub(&triage_config.common, review_info)
-        .wrap_err("failed to render markdown stub")?;
+    #[test]
+    fn partitions_p_high_issues_by_team_and_owner() {
+        let dir = TestDir::new("p-high-triage");
+        let (config, common) = test_util::configs(&dir);
+        let source = InMemorySource::new(vec![
+            test_util::issue(4, &["P-high", "T-compiler"], &["someone"]),
+            test_util::issue(3, &["P-high", "T-compiler", "WG-llvm", "WG-unknown"], &[]),
+            test_util::issue(2, &["P-high", "T-compiler"], &[]),
+            test_util::issue(1, &["P-high"], &[]),
+        ]);
 
-    fs::write(&triage_config.common.markdown_stub_path, &stub).wrap_err_with(|| {
-        format!("failed to write markdown stub to `{}`", triage_config.common.markdown_stub_path)
-    })?;
+        perform_triage(&config, &PHighTriage { common }, &source).unwrap();
 
-    Ok(())
+        let stub = dir.read("stub.md");
+        let section = |heading: &str| {
+            let start = stub.find(&format!("## {heading}")).unwrap();
+            let end = stub[start + 1..].find("\n## ").map_or(stub.len(), |end| start + 1 + end);
+            stub[start..end].matches("### #").count()
+        };
+        assert_eq!(section("P-high missing team label"), 1);
+        assert!(stub.contains("### #1: Issue 1"));
+        assert_eq!(section("P-high T-compiler issues missing owner"), 1);
+        assert_eq!(section("P-high T-compiler issues with owner"), 2);
+        assert!(stub.find("### #3").unwrap() < stub.find("### #4").unwrap());
+        // Only labels of working groups in the taxonomy are working groups.
+        assert!(stub.contains("Working groups: `WG-llvm`\n"));
+    }
 }
@@ -0,0 +1,203 @@
+//! Trend of the P-high backlog over the P-high triage runs recorded in the history database.
+//!
+//! The partitions are recomputed from each run's issues with the current team taxonomy, so counts
+//! of older runs may differ slightly from what their stubs showed if the taxonomy changed since.
+
+use std::fmt::Write as _;
+use std::fs;
+
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::*;
+
+use super::TRIAGE_KIND;
+use super::markdown_stub::ReviewInfo;
+use crate::config::{CommonConfig, PHighTrendConfig};
+use crate::history::History;
+use crate::team::TeamTaxonomy;
+
+pub(crate) fn perform_trend_report(
+    config: &CommonConfig,
+    trend_config: &PHighTrendConfig,
+) -> EResult<()> {
+    let taxonomy = TeamTaxonomy::load(config)?;
+    let history = History::open_read_only(&trend_config.history_db)?;
+
+    let runs = history.runs(TRIAGE_KIND)?;
+    info!("Recorded P-high triage runs count: {}", runs.len());
+
+    let trend = runs
+        .iter()
+        .map(|run| {
+            let issues = history.issues(run.id)?;
+            Ok(TrendPoint::new(run.collected_at, ReviewInfo::new(&issues, &taxonomy)))
+        })
+        .collect::<EResult<Vec<_>>>()?;
+
+    info!("Writing markdown report to `{}`", trend_config.markdown_report_path);
+    let report = render_markdown_report(&trend_config.markdown_report_title, &trend)
+        .wrap_err("failed to render markdown report")?;
+    fs::write(&trend_config.markdown_report_path, &report).wrap_err_with(|| {
+        format!("failed to write markdown report to `{}`", trend_config.markdown_report_path)
+    })?;
+
+    if let Some(csv_path) = &trend_config.csv_path {
+        info!("Writing CSV report to `{csv_path}`");
+        let csv = render_csv(&trend).wrap_err("failed to render CSV report")?;
+        fs::write(csv_path, &csv)
+            .wrap_err_with(|| format!("failed to write CSV report to `{csv_path}`"))?;
+    }
+
+    Ok(())
+}
+
+/// The P-high backlog as collected by one triage run.
+struct TrendPoint {
+    collected_at: OffsetDateTime,
+    p_high: usize,
+    t_compiler_p_high: usize,
+    no_owner: usize,
+    no_team: usize,
+    /// Median age in days of the P-high issues at the time of the run, `None` if there were none.
+    median_age_days: Option<i64>,
+}
+
+impl TrendPoint {
+    fn new(collected_at: OffsetDateTime, info: ReviewInfo<'_>) -> Self {
+        let (no_owner, has_owner) = info.t_compiler_p_high_partition_by_ownership();
+
+        let mut ages = info
+            .p_high
+            .iter()
+            .map(|issue| (collected_at - issue.created_at).whole_days())
+            .collect::<Vec<_>>();
+        ages.sort_unstable();
+        let median_age_days = match ages.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(ages[len / 2]),
+            len => Some((ages[len / 2 - 1] + ages[len / 2]) / 2),
+        };
+
+        Self {
+            collected_at,
+            p_high: info.p_high.len(),
+            t_compiler_p_high: no_owner.len() + has_owner.len(),
+            no_owner: no_owner.len(),
+            no_team: info.p_high_no_team().len(),
+            median_age_days,
+        }
+    }
+}
+
+fn render_markdown_report(title: &str, trend: &[TrendPoint]) -> EResult<String> {
+    let mut buf = String::new();
+    writeln!(&mut buf, "<!-- report generated with pcr-util -->\n")?;
+    writeln!(&mut buf, "# {title}\n")?;
+
+    if trend.is_empty() {
+        writeln!(&mut buf, "**No P-high triage runs recorded**")?;
+        return Ok(buf);
+    }
+
+    writeln!(
+        &mut buf,
+        "| Collected on | P-high | T-compiler P-high | T-compiler P-high without owner | P-high without team | Median age (days) |"
+    )?;
+    writeln!(&mut buf, "|---|---|---|---|---|---|")?;
+    for point in trend {
+        writeln!(
+            &mut buf,
+            "| {} | {} | {} | {} | {} | {} |",
+            point.collected_at.date(),
+            point.p_high,
+            point.t_compiler_p_high,
+            point.no_owner,
+            point.no_team,
+            point.median_age_days.map_or_else(|| "-".to_owned(), |age| age.to_string()),
+        )?;
+    }
+
+    Ok(buf)
+}
+
+fn render_csv(trend: &[TrendPoint]) -> EResult<String> {
+    let mut buf = String::new();
+    writeln!(
+        &mut buf,
+        "collected_at,p_high,t_compiler_p_high,t_compiler_p_high_no_owner,p_high_no_team,median_age_days"
+    )?;
+    for point in trend {
+        writeln!(
+            &mut buf,
+            "{},{},{},{},{},{}",
+            point.collected_at.format(&Rfc3339)?,
+            point.p_high,
+            point.t_compiler_p_high,
+            point.no_owner,
+            point.no_team,
+            point.median_age_days.map(|age| age.to_string()).unwrap_or_default(),
+        )?;
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::test_util::{self, TestDir};
+    use crate::triage_item;
+
+    #[test]
+    fn reports_counts_and_median_age_per_run() {
+        let dir = TestDir::new("p-high-trend");
+        let (config, _) = test_util::configs(&dir);
+        let history_db = dir.path().join("history.db");
+        let mut history = History::open(&history_db).unwrap();
+
+        // Created 2024-11-06, and 5 and 10 days earlier.
+        let mut issues = triage_item::simplify_repr(vec![
+            test_util::issue(1, &["P-high", "T-compiler"], &[]),
+            test_util::issue(2, &["P-high", "T-compiler"], &["jane"]),
+            test_util::issue(3, &["P-high"], &[]),
+        ]);
+        issues[1].created_at = datetime!(2024-11-01 02:24:31 UTC);
+        issues[2].created_at = datetime!(2024-10-27 02:24:31 UTC);
+        history.record_run(TRIAGE_KIND, datetime!(2024-11-16 12:00 UTC), &issues).unwrap();
+        issues.pop();
+        issues[0].assignees.push("john".to_owned());
+        history.record_run(TRIAGE_KIND, datetime!(2024-12-06 12:00 UTC), &issues).unwrap();
+        history.record_run(TRIAGE_KIND, datetime!(2025-01-01 12:00 UTC), &[]).unwrap();
+        drop(history);
+
+        let trend_config = PHighTrendConfig {
+            history_db,
+            markdown_report_title: "P-high trend".to_owned(),
+            markdown_report_path: dir.path().join("trend.md"),
+            csv_path: Some(dir.path().join("trend.csv")),
+        };
+        perform_trend_report(&config, &trend_config).unwrap();
+
+        let report = dir.read("trend.md");
+        let rows = report.lines().filter(|line| line.starts_with("| 20")).collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                "| 2024-11-16 | 3 | 2 | 1 | 1 | 15 |",
+                "| 2024-12-06 | 2 | 2 | 0 | 0 | 32 |",
+                "| 2025-01-01 | 0 | 0 | 0 | 0 | - |",
+            ]
+        );
+        assert_eq!(
+            dir.read("trend.csv").lines().skip(1).collect::<Vec<_>>(),
+            [
+                "2024-11-16T12:00:00Z,3,2,1,1,15",
+                "2024-12-06T12:00:00Z,2,2,0,0,32",
+                "2025-01-01T12:00:00Z,0,0,0,0,",
+            ]
+        );
+    }
+}
@@ -0,0 +1,5 @@
+use crate::query::IssueQuery;
+
+pub(crate) fn prioritize_query() -> IssueQuery {
+    IssueQuery::new("issues to prioritize").sort("created-asc").label("I-prioritize")
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use super::cmd;
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::markdown_stub::{IssueField, IssueLayout, RenderCtxt};
+use crate::regression::REGRESSION_LABELS;
+use crate::team::TeamTaxonomy;
+use crate::triage_item::label_matches;
+
+/// Label heuristics for suggesting a priority, from highest to lowest priority (see
+/// [`label_matches`] for the pattern syntax). The highest priority any label suggests wins.
+const PRIORITY_HEURISTICS: &[(&str, &str)] = &[
+    ("I-unsound", "P-high"),
+    ("I-miscompile", "P-high"),
+    ("I-ICE", "P-high"),
+    // From stable to stable, to beta and to nightly.
+    (REGRESSION_LABELS[0], "P-high"),
+    (REGRESSION_LABELS[1], "P-high"),
+    (REGRESSION_LABELS[2], "P-medium"),
+    ("I-crash", "P-medium"),
+    ("I-slow", "P-medium"),
+    ("I-compiletime", "P-medium"),
+    ("A-diagnostics", "P-low"),
+    ("D-*", "P-low"),
+];
+
+/// Group heading for issues where none of the heuristics matched.
+const NO_SUGGESTION: &str = "no suggestion";
+
+/// The priority the labels of an issue suggest, and the labels suggesting it.
+pub(crate) struct SuggestedPriority<'a> {
+    pub(crate) priority: &'static str,
+    pub(crate) labels: Vec<&'a str>,
+}
+
+pub(crate) struct ReviewInfo<'a> {
+    pub(crate) issues: &'a [TriageItem],
+}
+
+impl<'a> ReviewInfo<'a> {
+    pub(crate) fn new(issues: &'a [TriageItem]) -> Self {
+        Self { issues }
+    }
+
+    /// Group issues by suggested priority, from highest to lowest. Issues without a suggestion are
+    /// grouped last.
+    pub(crate) fn group_by_suggested_priority(
+        &'a self,
+    ) -> Vec<(&'static str, Vec<&'a TriageItem>)> {
+        let mut groups: BTreeMap<usize, (&'static str, Vec<&'a TriageItem>)> = BTreeMap::new();
+        for issue in self.issues {
+            let (rank, priority) = match suggested_priority(issue) {
+                Some(suggestion) => (priority_rank(suggestion.priority), suggestion.priority),
+                None => (usize::MAX, NO_SUGGESTION),
+            };
+            groups.entry(rank).or_insert_with(|| (priority, vec![])).1.push(issue);
+        }
+        groups.into_values().collect()
+    }
+}
+
+fn priority_rank(priority: &str) -> usize {
+    PRIORITY_HEURISTICS.iter().position(|&(_, p)| p == priority).unwrap_or(usize::MAX)
+}
+
+pub(crate) fn suggested_priority(issue: &TriageItem) -> Option<SuggestedPriority<'_>> {
+    let priority = PRIORITY_HEURISTICS.iter().find_map(|&(pattern, priority)| {
+        issue.labels.iter().any(|label| label_matches(label, pattern)).then_some(priority)
+    })?;
+    let labels = issue
+        .labels
+        .iter()
+        .filter(|label| {
+            PRIORITY_HEURISTICS
+                .iter()
+                .any(|&(pattern, p)| p == priority && label_matches(label, pattern))
+        })
+        .map(String::as_str)
+        .collect();
+    Some(SuggestedPriority { priority, labels })
+}
+
+pub(crate) fn render_markdown_stub<'a>(
+    config: &CommonTriageConfig,
+    taxonomy: &TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    info: ReviewInfo<'a>,
+) -> EResult<String> {
+    let layout = config.issue_layout.unwrap_or(IssueLayout::FieldList);
+    let mut ctx = RenderCtxt::new(config, taxonomy, collected_at, layout, IssueField::DEFAULT)?;
+    ctx.enable_proposed_priority_slot();
+    for issue in info.issues {
+        if let Some(suggestion) = suggested_priority(issue) {
+            let labels =
+                suggestion.labels.iter().map(|label| format!("`{label}`")).collect::<Vec<_>>();
+            let flag = format!(
+                "Suggested priority: {} (because of {})",
+                suggestion.priority,
+                labels.join(", ")
+            );
+            ctx.flag_issue(issue.number, flag);
+        }
+    }
+    ctx.render_document_header()?;
+    ctx.render_query_link(&cmd::prioritize_query())?;
+
+    for (priority, issues) in info.group_by_suggested_priority() {
+        let heading = match priority {
+            NO_SUGGESTION => "Issues to prioritize (no suggestion)".to_owned(),
+            priority => format!("Issues to prioritize (likely {priority})"),
+        };
+        ctx.render_section(&heading, None, None, &issues)?;
+    }
+
+    Ok(ctx.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, triage_item};
+
+    fn issues(labels: &[&[&str]]) -> Vec<TriageItem> {
+        let reprs = labels
+            .iter()
+            .zip(1..)
+            .map(|(labels, number)| test_util::issue(number, labels, &[]))
+            .collect();
+        triage_item::simplify_repr(reprs)
+    }
+
+    fn suggestion(labels: &[&str]) -> Option<(&'static str, Vec<String>)> {
+        let issues = issues(&[labels]);
+        suggested_priority(&issues[0]).map(|suggestion| {
+            let labels = suggestion.labels.iter().map(|label| label.to_string()).collect();
+            (suggestion.priority, labels)
+        })
+    }
+
+    #[test]
+    fn highest_suggested_priority_wins() {
+        assert_eq!(
+            suggestion(&["A-diagnostics", "I-slow", "I-ICE", "T-compiler"]),
+            Some(("P-high", vec!["I-ICE".to_owned()]))
+        );
+        assert_eq!(
+            suggestion(&["regression-from-stable-to-nightly", "A-diagnostics"]),
+            Some(("P-medium", vec!["regression-from-stable-to-nightly".to_owned()]))
+        );
+        assert_eq!(suggestion(&["T-compiler", "regression-untriaged"]), None);
+    }
+
+    #[test]
+    fn diagnostic_labels_match_by_prefix() {
+        assert_eq!(
+            suggestion(&["D-confusing", "D-papercut"]),
+            Some(("P-low", vec!["D-confusing".to_owned(), "D-papercut".to_owned()]))
+        );
+        assert_eq!(suggestion(&["CD-something"]), None);
+    }
+
+    #[test]
+    fn names_only_labels_suggesting_winning_priority() {
+        assert_eq!(
+            suggestion(&[
+                "I-unsound",
+                "I-crash",
+                "regression-from-stable-to-beta",
+                "D-confusing",
+                "I-ICE"
+            ]),
+            Some((
+                "P-high",
+                vec![
+                    "I-ICE".to_owned(),
+                    "I-unsound".to_owned(),
+                    "regression-from-stable-to-beta".to_owned()
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn groups_from_highest_priority_to_no_suggestion() {
+        let issues = issues(&[
+            &["T-compiler"],
+            &["A-diagnostics"],
+            &["I-slow"],
+            &["I-miscompile"],
+            &["D-confusing"],
+            &["I-ICE"],
+        ]);
+        let info = ReviewInfo::new(&issues);
+
+        let groups = info
+            .group_by_suggested_priority()
+            .into_iter()
+            .map(|(priority, issues)| {
+                (priority, issues.iter().map(|issue| issue.number).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            [
+                ("P-high", vec![4, 6]),
+                ("P-medium", vec![3]),
+                ("P-low", vec![2, 5]),
+                (NO_SUGGESTION, vec![1])
+            ]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+mod cmd;
+mod markdown_stub;
+
+use self::markdown_stub::ReviewInfo;
+use crate::EResult;
+use crate::config::{CommonConfig, PrioritizeTriage};
+use crate::issue_source::IssueSource;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "i-prioritize";
+
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &PrioritizeTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let spec = TriageSpec {
+        kind: TRIAGE_KIND,
+        description: "Issues to prioritize",
+        queries: &[cmd::prioritize_query()],
+    };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |_| Ok(()),
+        |collected_at, issues| {
+            let review_info = ReviewInfo::new(issues);
+            let taxonomy = TeamTaxonomy::load(config)?;
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                review_info,
+            )
+        },
+    )
+}
@@ -0,0 +1,202 @@
+//! GitHub issue and pull request search queries, used both to fetch them via `gh` and to link to
+//! the same result set in the browser.
+
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+
+/// Characters to percent-encode in a GitHub search query parameter.
+const SEARCH_QUERY_ENCODE_SET: &AsciiSet =
+    &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Whether a query searches issues or pull requests.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum QueryKind {
+    Issues,
+    PullRequests,
+}
+
+impl QueryKind {
+    /// The `gh` subcommand listing this kind, e.g. `gh pr list`.
+    pub(crate) fn gh_command(self) -> &'static str {
+        match self {
+            QueryKind::Issues => "issue",
+            QueryKind::PullRequests => "pr",
+        }
+    }
+
+    fn search_qualifier(self) -> &'static str {
+        match self {
+            QueryKind::Issues => "is:issue",
+            QueryKind::PullRequests => "is:pr",
+        }
+    }
+
+    /// Path of the repository's search page for this kind.
+    fn browser_path(self) -> &'static str {
+        match self {
+            QueryKind::Issues => "issues",
+            QueryKind::PullRequests => "pulls",
+        }
+    }
+}
+
+/// A search for `rust-lang/rust` issues or pull requests, open ones unless stated otherwise, built
+/// up from search qualifiers, e.g.
+///
+/// ```ignore
+/// IssueQuery::new("P-high issues without assignee").label("P-high").no_assignee()
+/// ```
+#[derive(Debug, Clone)]
+pub(crate) struct IssueQuery {
+    /// What kind of issues this query collects, e.g. "P-high issues".
+    pub(crate) description: String,
+    pub(crate) kind: QueryKind,
+    /// Whether only open issues are searched.
+    pub(crate) open_only: bool,
+    terms: Vec<String>,
+    /// Default maximum number of issues to collect, can be overridden with `--limit`.
+    pub(crate) limit: u32,
+}
+
+impl IssueQuery {
+    pub(crate) fn new(description: impl Into<String>) -> Self {
+        Self::of_kind(QueryKind::Issues, description)
+    }
+
+    /// A search for open pull requests instead of issues.
+    pub(crate) fn pull_requests(description: impl Into<String>) -> Self {
+        Self::of_kind(QueryKind::PullRequests, description)
+    }
+
+    fn of_kind(kind: QueryKind, description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            kind,
+            open_only: true,
+            terms: vec![kind.search_qualifier().to_owned(), "is:open".to_owned()],
+            limit: 1000,
+        }
+    }
+
+    /// Also search closed issues, or closed and merged pull requests.
+    pub(crate) fn any_state(mut self) -> Self {
+        self.open_only = false;
+        self.terms.retain(|term| term != "is:open");
+        self
+    }
+
+    /// Describe a query derived from another query.
+    pub(crate) fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub(crate) fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Issues must have `label`.
+    pub(crate) fn label(mut self, label: &str) -> Self {
+        self.terms.push(format!("label:{}", quote(label)));
+        self
+    }
+
+    /// Issues must have at least one of `labels`.
+    pub(crate) fn any_label(mut self, labels: &[&str]) -> Self {
+        let labels = labels.iter().map(|label| quote(label)).collect::<Vec<_>>();
+        self.terms.push(format!("label:{}", labels.join(",")));
+        self
+    }
+
+    /// Issues must not have `label`.
+    pub(crate) fn without_label(mut self, label: &str) -> Self {
+        self.terms.push(format!("-label:{}", quote(label)));
+        self
+    }
+
+    /// Issues must have none of `labels`.
+    pub(crate) fn without_labels<'a>(self, labels: impl IntoIterator<Item = &'a str>) -> Self {
+        labels.into_iter().fold(self, Self::without_label)
+    }
+
+    pub(crate) fn no_assignee(mut self) -> Self {
+        self.terms.push("no:assignee".to_owned());
+        self
+    }
+
+    /// Sort results, e.g. by `created-asc` or `updated-asc`.
+    pub(crate) fn sort(mut self, sort: &str) -> Self {
+        self.terms.push(format!("sort:{sort}"));
+        self
+    }
+
+    /// Arbitrary additional search query.
+    pub(crate) fn raw(mut self, search: &str) -> Self {
+        self.terms.extend(search.split_whitespace().map(str::to_owned));
+        self
+    }
+
+    /// The GitHub search query, as passed to `gh issue list --search` or `gh pr list --search`.
+    pub(crate) fn search(&self) -> String {
+        self.terms.join(" ")
+    }
+
+    /// Link to the same search results in the browser.
+    pub(crate) fn browser_url(&self) -> String {
+        format!(
+            "https://github.com/rust-lang/rust/{}?q={}",
+            self.kind.browser_path(),
+            utf8_percent_encode(&self.search(), SEARCH_QUERY_ENCODE_SET)
+        )
+    }
+}
+
+fn quote(label: &str) -> String {
+    if label.contains(char::is_whitespace) { format!("\"{label}\"") } else { label.to_owned() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_labels_containing_spaces() {
+        let query = IssueQuery::new("test")
+            .label("P-high")
+            .label("beta-nominated pending")
+            .without_label("S-blocked on design");
+        assert_eq!(
+            query.search(),
+            "is:issue is:open label:P-high label:\"beta-nominated pending\" \
+            -label:\"S-blocked on design\""
+        );
+    }
+
+    #[test]
+    fn any_label_searches_comma_separated_labels() {
+        let query = IssueQuery::new("test").any_label(&["P-critical", "P-high", "needs triage"]);
+        assert_eq!(query.search(), "is:issue is:open label:P-critical,P-high,\"needs triage\"");
+    }
+
+    #[test]
+    fn any_state_drops_open_qualifier() {
+        let query = IssueQuery::pull_requests("test").label("beta-nominated").any_state();
+        assert!(!query.open_only);
+        assert_eq!(query.search(), "is:pr label:beta-nominated");
+    }
+
+    #[test]
+    fn browser_url_percent_encodes_search() {
+        let issues = IssueQuery::new("test").label("needs triage").sort("created-asc");
+        assert_eq!(
+            issues.browser_url(),
+            "https://github.com/rust-lang/rust/issues?q=is%3Aissue%20is%3Aopen%20\
+            label%3A%22needs%20triage%22%20sort%3Acreated-asc"
+        );
+        let prs = IssueQuery::pull_requests("test").no_assignee();
+        assert_eq!(
+            prs.browser_url(),
+            "https://github.com/rust-lang/rust/pulls?q=is%3Apr%20is%3Aopen%20no%3Aassignee"
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use super::{PRIORITY_LABELS, REGRESSION_LABELS};
+use crate::query::IssueQuery;
+
+pub(crate) fn regression_query() -> IssueQuery {
+    IssueQuery::new("regressions").sort("created-asc").any_label(REGRESSION_LABELS)
+}
+
+/// Regressions labelled `label`, with or without a priority label. Like the stub's sections,
+/// leaves out regressions which also have a more severe regression label.
+pub(crate) fn regression_kind_query(label: &str, prioritized: bool) -> IssueQuery {
+    let more_severe = REGRESSION_LABELS.iter().copied().take_while(|&l| l != label);
+    let query = IssueQuery::new(label).sort("created-asc").label(label).without_labels(more_severe);
+    if prioritized {
+        query.with_description(format!("{label} issues with priority")).any_label(PRIORITY_LABELS)
+    } else {
+        query
+            .with_description(format!("{label} issues missing priority"))
+            .without_labels(PRIORITY_LABELS.iter().copied())
+    }
+}
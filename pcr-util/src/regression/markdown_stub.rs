@@ -0,0 +1,149 @@
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use super::{PRIORITY_LABELS, REGRESSION_LABELS, cmd};
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::markdown_stub::{IssueField, IssueLayout, RenderCtxt};
+use crate::team::TeamTaxonomy;
+
+pub(crate) struct ReviewInfo<'a> {
+    pub(crate) regressions: &'a [TriageItem],
+}
+
+impl<'a> ReviewInfo<'a> {
+    pub(crate) fn new(regressions: &'a [TriageItem]) -> Self {
+        Self { regressions }
+    }
+
+    /// Regressions labelled `label`, partitioned by whether they have a priority label. Returns
+    /// `(prioritized, missing priority)`.
+    ///
+    /// An issue with several regression labels is only in the partition of the first of
+    /// [`REGRESSION_LABELS`] it has.
+    pub(crate) fn partition_by_priority(
+        &'a self,
+        label: &str,
+    ) -> (Vec<&'a TriageItem>, Vec<&'a TriageItem>) {
+        self.regressions
+            .iter()
+            .filter(|issue| regression_kind(issue) == Some(label))
+            .partition(|issue| has_priority(issue))
+    }
+}
+
+fn regression_kind(issue: &TriageItem) -> Option<&'static str> {
+    REGRESSION_LABELS.iter().copied().find(|label| issue.labels.iter().any(|l| l == label))
+}
+
+fn has_priority(issue: &TriageItem) -> bool {
+    issue.labels.iter().any(|label| PRIORITY_LABELS.contains(&label.as_str()))
+}
+
+/// What the triage should fix about regression `issue`'s labels.
+fn flags(issue: &TriageItem, taxonomy: &TeamTaxonomy) -> Vec<&'static str> {
+    let mut flags = vec![];
+    if !has_priority(issue) {
+        flags.push("Missing priority label");
+    }
+    if !taxonomy.has_team(&issue.labels) {
+        flags.push("Missing team label");
+    }
+    flags
+}
+
+pub(crate) fn render_markdown_stub<'a>(
+    config: &CommonTriageConfig,
+    taxonomy: &TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    info: ReviewInfo<'a>,
+) -> EResult<String> {
+    let layout = config.issue_layout.unwrap_or(IssueLayout::FieldList);
+    let mut ctx = RenderCtxt::new(config, taxonomy, collected_at, layout, IssueField::DEFAULT)?;
+    for issue in info.regressions {
+        for flag in flags(issue, taxonomy) {
+            ctx.flag_issue(issue.number, flag.to_owned());
+        }
+    }
+    ctx.render_document_header()?;
+    ctx.render_query_link(&cmd::regression_query())?;
+
+    for label in REGRESSION_LABELS {
+        let (prioritized, missing_priority) = info.partition_by_priority(label);
+        ctx.render_section(
+            &format!("`{label}` missing priority"),
+            Some(&cmd::regression_kind_query(label, false)),
+            Some("Did not find regressions without a priority label"),
+            &missing_priority,
+        )?;
+        ctx.render_section(
+            &format!("`{label}` with priority"),
+            Some(&cmd::regression_kind_query(label, true)),
+            None,
+            &prioritized,
+        )?;
+    }
+
+    Ok(ctx.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util, triage_item};
+
+    fn regressions() -> Vec<TriageItem> {
+        triage_item::simplify_repr(vec![
+            test_util::issue(1, &["regression-from-stable-to-stable", "P-high", "T-compiler"], &[]),
+            test_util::issue(2, &["regression-from-stable-to-stable", "T-compiler"], &[]),
+            test_util::issue(
+                3,
+                &["regression-from-stable-to-beta", "regression-from-stable-to-stable", "P-low"],
+                &[],
+            ),
+            test_util::issue(4, &["regression-from-stable-to-beta", "P-critical"], &[]),
+            test_util::issue(5, &["regression-untriaged", "P-high-ish", "T-not-a-team"], &[]),
+        ])
+    }
+
+    fn numbers(issues: &[&TriageItem]) -> Vec<u64> {
+        issues.iter().map(|issue| issue.number).collect()
+    }
+
+    #[test]
+    fn partitions_by_most_severe_regression_label_and_priority() {
+        let regressions = regressions();
+        let info = ReviewInfo::new(&regressions);
+
+        let (prioritized, missing) = info.partition_by_priority("regression-from-stable-to-stable");
+        assert_eq!((numbers(&prioritized), numbers(&missing)), (vec![1, 3], vec![2]));
+        let (prioritized, missing) = info.partition_by_priority("regression-from-stable-to-beta");
+        assert_eq!((numbers(&prioritized), numbers(&missing)), (vec![4], vec![]));
+        let (prioritized, missing) = info.partition_by_priority("regression-untriaged");
+        assert_eq!((numbers(&prioritized), numbers(&missing)), (vec![], vec![5]));
+    }
+
+    #[test]
+    fn section_queries_leave_out_more_severe_regressions() {
+        let search = cmd::regression_kind_query("regression-from-stable-to-beta", true).search();
+        assert!(search.contains("-label:regression-from-stable-to-stable"));
+        assert!(!search.contains("-label:regression-from-stable-to-beta"));
+        assert!(!search.contains("-label:regression-from-stable-to-nightly"));
+        let search = cmd::regression_kind_query("regression-from-stable-to-stable", false).search();
+        assert!(!search.contains("-label:regression"));
+    }
+
+    #[test]
+    fn flags_missing_priority_and_team_labels() {
+        let regressions = regressions();
+        let taxonomy = TeamTaxonomy::builtin();
+        let flags_of = |number| {
+            let issue = regressions.iter().find(|issue| issue.number == number).unwrap();
+            flags(issue, &taxonomy)
+        };
+        assert!(flags_of(1).is_empty());
+        assert_eq!(flags_of(2), ["Missing priority label"]);
+        assert_eq!(flags_of(4), ["Missing team label"]);
+        assert_eq!(flags_of(5), ["Missing priority label", "Missing team label"]);
+    }
+}
@@ -0,0 +1,51 @@
+mod cmd;
+mod markdown_stub;
+
+use self::markdown_stub::ReviewInfo;
+use crate::EResult;
+use crate::config::{CommonConfig, RegressionTriage};
+use crate::issue_source::IssueSource;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "regression";
+
+/// Regression labels, from most to least severe. Each gets its own sections in the stub.
+pub(crate) const REGRESSION_LABELS: &[&str] = &[
+    "regression-from-stable-to-stable",
+    "regression-from-stable-to-beta",
+    "regression-from-stable-to-nightly",
+    "regression-untriaged",
+];
+
+const PRIORITY_LABELS: &[&str] = &["P-critical", "P-high", "P-medium", "P-low"];
+
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &RegressionTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let spec = TriageSpec {
+        kind: TRIAGE_KIND,
+        description: "Regressions",
+        queries: &[cmd::regression_query()],
+    };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |_| Ok(()),
+        |collected_at, regressions| {
+            let review_info = ReviewInfo::new(regressions);
+            let taxonomy = TeamTaxonomy::load(config)?;
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                review_info,
+            )
+        },
+    )
+}
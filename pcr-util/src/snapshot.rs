@@ -0,0 +1,86 @@
+//! Issue snapshots persisted to `--persist-path` by previous triage runs.
+
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::TriageItem;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct Snapshot {
+    #[serde(with = "time::serde::rfc3339")]
+    pub(crate) collected_at: OffsetDateTime,
+    pub(crate) issues: Vec<TriageItem>,
+}
+
+/// Like [`Snapshot`], to persist issues without taking ownership of them.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct SnapshotRef<'a> {
+    #[serde(with = "time::serde::rfc3339")]
+    collected_at: OffsetDateTime,
+    issues: &'a [TriageItem],
+}
+
+/// Snapshots persisted before the collection time was recorded are bare arrays of issues.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SnapshotRepr {
+    Snapshot(Snapshot),
+    Issues(Vec<TriageItem>),
+}
+
+pub(crate) fn save(
+    path: &Utf8Path,
+    collected_at: OffsetDateTime,
+    issues: &[TriageItem],
+) -> EResult<()> {
+    let json = serde_json::to_vec_pretty(&SnapshotRef { collected_at, issues })?;
+    fs::write(path, &json).wrap_err_with(|| format!("failed to write response to `{path}`"))
+}
+
+pub(crate) fn load(path: &Utf8Path) -> EResult<Snapshot> {
+    let json = fs::read(path).wrap_err_with(|| format!("failed to read snapshot `{path}`"))?;
+    let repr = serde_json::from_slice(&json)
+        .wrap_err_with(|| format!("failed to deserialize snapshot `{path}` as triage items"))?;
+    match repr {
+        SnapshotRepr::Snapshot(snapshot) => Ok(snapshot),
+        SnapshotRepr::Issues(issues) => {
+            // Best guess at when an old snapshot was collected.
+            let collected_at = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .wrap_err_with(|| {
+                    format!("failed to obtain modification time of snapshot `{path}`")
+                })?
+                .into();
+            Ok(Snapshot { collected_at, issues })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, TestDir};
+    use crate::triage_item;
+
+    #[test]
+    fn loads_bare_array_snapshot() {
+        let dir = TestDir::new("bare-array-snapshot");
+        let path = dir.path().join("snapshot.json");
+        let issues = triage_item::simplify_repr(vec![test_util::issue(7, &["P-high"], &[])]);
+        fs::write(&path, serde_json::to_vec(&issues).unwrap()).unwrap();
+
+        let snapshot = load(&path).unwrap();
+
+        let numbers = snapshot.issues.iter().map(|issue| issue.number).collect::<Vec<_>>();
+        assert_eq!(numbers, [7]);
+        let modified: OffsetDateTime = fs::metadata(&path).unwrap().modified().unwrap().into();
+        assert_eq!(snapshot.collected_at, modified);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::Write as _;
+
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use super::{IssueChange, SnapshotDiff};
+use crate::TriageItem;
+use crate::config::SnapshotDiffConfig;
+use crate::markdown_stub::render_comma_sep_inline_code_item;
+use crate::snapshot::Snapshot;
+
+pub(crate) fn render_markdown_report(
+    config: &SnapshotDiffConfig,
+    old: &Snapshot,
+    new: &Snapshot,
+    diff: &SnapshotDiff<'_>,
+) -> EResult<String> {
+    let mut ctx = RenderCtxt::new(config);
+    ctx.render_markdown_report(old, new, diff)?;
+    Ok(ctx.finish())
+}
+
+struct RenderCtxt<'c> {
+    config: &'c SnapshotDiffConfig,
+    buf: String,
+}
+
+impl<'c> RenderCtxt<'c> {
+    fn new(config: &'c SnapshotDiffConfig) -> Self {
+        Self { buf: String::new(), config }
+    }
+
+    fn finish(self) -> String {
+        self.buf
+    }
+
+    fn render_markdown_report(
+        &mut self,
+        old: &Snapshot,
+        new: &Snapshot,
+        diff: &SnapshotDiff<'_>,
+    ) -> EResult<()> {
+        self.render_document_header(
+            &self.config.markdown_report_title,
+            old.collected_at,
+            new.collected_at,
+        )?;
+
+        self.render_issue_list("New issues", "No new issues", &diff.new)?;
+        self.render_issue_list(
+            "Gone issues (closed or relabelled)",
+            "No issues are gone",
+            &diff.gone,
+        )?;
+        self.render_changed(&diff.changed)?;
+        self.render_issue_list("Updated issues", "No issues were updated", &diff.updated)?;
+
+        Ok(())
+    }
+
+    fn render_document_header(
+        &mut self,
+        title: &str,
+        old: OffsetDateTime,
+        new: OffsetDateTime,
+    ) -> EResult<()> {
+        writeln!(&mut self.buf, "<!-- report generated with pcr-util -->\n")?;
+        writeln!(&mut self.buf, "# {title}\n")?;
+
+        let format = &time::format_description::well_known::Rfc3339;
+        writeln!(
+            &mut self.buf,
+            "*Changes between issues snapshots collected on {} and {}*\n\n",
+            old.format(format).unwrap(),
+            new.format(format).unwrap()
+        )?;
+        Ok(())
+    }
+
+    fn render_issue_list(
+        &mut self,
+        heading: &str,
+        empty: &str,
+        issues: &[&TriageItem],
+    ) -> EResult<()> {
+        writeln!(&mut self.buf, "## {heading}\n")?;
+        if issues.is_empty() {
+            writeln!(&mut self.buf, "**{empty}**")?;
+        }
+        for issue in issues {
+            writeln!(
+                &mut self.buf,
+                "- [#{}]({}): {} (last updated {})",
+                issue.number,
+                issue.url,
+                issue.title,
+                issue.updated_at.date()
+            )?;
+        }
+        write!(&mut self.buf, "\n\n")?;
+        Ok(())
+    }
+
+    fn render_changed(&mut self, changed: &[IssueChange<'_>]) -> EResult<()> {
+        writeln!(&mut self.buf, "## Issues with changed labels or assignees\n")?;
+        if changed.is_empty() {
+            writeln!(&mut self.buf, "**No labels or assignees changed**")?;
+        }
+        for change in changed {
+            writeln!(&mut self.buf, "### #{}: {}", change.issue.number, change.issue.title)?;
+            writeln!(&mut self.buf, "Link: <{}>", change.issue.url)?;
+            self.render_change("Labels added", &change.added_labels)?;
+            self.render_change("Labels removed", &change.removed_labels)?;
+            self.render_change("Assignees added", &change.added_assignees)?;
+            self.render_change("Assignees removed", &change.removed_assignees)?;
+            writeln!(&mut self.buf)?;
+        }
+        write!(&mut self.buf, "\n\n")?;
+        Ok(())
+    }
+
+    fn render_change(&mut self, kind: &str, items: &[&str]) -> EResult<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        write!(&mut self.buf, "{kind}: ")?;
+        render_comma_sep_inline_code_item(&mut self.buf, items)?;
+        writeln!(&mut self.buf)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+mod markdown_stub;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use color_eyre::eyre::Context;
+use tracing::*;
+
+use crate::config::SnapshotDiffConfig;
+use crate::snapshot::{self, Snapshot};
+use crate::{EResult, TriageItem};
+
+pub(crate) fn perform_diff(diff_config: &SnapshotDiffConfig) -> EResult<()> {
+    let old = snapshot::load(&diff_config.old_snapshot)?;
+    let new = snapshot::load(&diff_config.new_snapshot)?;
+
+    let diff = SnapshotDiff::new(&old, &new);
+    info!("New issues count: {}", diff.new.len());
+    info!("Gone issues count: {}", diff.gone.len());
+    info!("Changed issues count: {}", diff.changed.len());
+    info!("Updated issues count: {}", diff.updated.len());
+
+    info!("Writing markdown report to `{}`", diff_config.markdown_report_path);
+    let report = markdown_stub::render_markdown_report(diff_config, &old, &new, &diff)
+        .wrap_err("failed to render markdown report")?;
+
+    fs::write(&diff_config.markdown_report_path, &report).wrap_err_with(|| {
+        format!("failed to write markdown report to `{}`", diff_config.markdown_report_path)
+    })?;
+
+    Ok(())
+}
+
+/// What changed between two snapshots of the same triage kind.
+pub(crate) struct SnapshotDiff<'a> {
+    /// Issues only in the new snapshot.
+    pub(crate) new: Vec<&'a TriageItem>,
+    /// Issues only in the old snapshot, i.e. they were closed or no longer match the triage query.
+    pub(crate) gone: Vec<&'a TriageItem>,
+    /// Issues in both snapshots whose labels or assignees changed.
+    pub(crate) changed: Vec<IssueChange<'a>>,
+    /// Issues in both snapshots which were updated since the old snapshot, for any reason.
+    pub(crate) updated: Vec<&'a TriageItem>,
+}
+
+pub(crate) struct IssueChange<'a> {
+    pub(crate) issue: &'a TriageItem,
+    pub(crate) added_labels: Vec<&'a str>,
+    pub(crate) removed_labels: Vec<&'a str>,
+    pub(crate) added_assignees: Vec<&'a str>,
+    pub(crate) removed_assignees: Vec<&'a str>,
+}
+
+impl<'a> SnapshotDiff<'a> {
+    pub(crate) fn new(old: &'a Snapshot, new: &'a Snapshot) -> Self {
+        let old_issues = by_number(&old.issues);
+        let new_issues = by_number(&new.issues);
+
+        let mut diff = SnapshotDiff { new: vec![], gone: vec![], changed: vec![], updated: vec![] };
+        for (number, new_issue) in &new_issues {
+            let Some(old_issue) = old_issues.get(number) else {
+                diff.new.push(new_issue);
+                continue;
+            };
+            if new_issue.updated_at > old_issue.updated_at {
+                diff.updated.push(new_issue);
+            }
+            let (added_labels, removed_labels) = set_diff(&old_issue.labels, &new_issue.labels);
+            let (added_assignees, removed_assignees) =
+                set_diff(&old_issue.assignees, &new_issue.assignees);
+            if !(added_labels.is_empty()
+                && removed_labels.is_empty()
+                && added_assignees.is_empty()
+                && removed_assignees.is_empty())
+            {
+                diff.changed.push(IssueChange {
+                    issue: new_issue,
+                    added_labels,
+                    removed_labels,
+                    added_assignees,
+                    removed_assignees,
+                });
+            }
+        }
+        diff.gone = old_issues
+            .iter()
+            .filter(|(number, _)| !new_issues.contains_key(number))
+            .map(|(_, issue)| *issue)
+            .collect();
+
+        diff
+    }
+}
+
+fn by_number(issues: &[TriageItem]) -> BTreeMap<u64, &TriageItem> {
+    issues.iter().map(|issue| (issue.number, issue)).collect()
+}
+
+/// Returns `(added, removed)` items going from `old` to `new`.
+fn set_diff<'a>(old: &'a [String], new: &'a [String]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let old = old.iter().map(String::as_str).collect::<BTreeSet<_>>();
+    let new = new.iter().map(String::as_str).collect::<BTreeSet<_>>();
+    (new.difference(&old).copied().collect(), old.difference(&new).copied().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{test_util, triage_item};
+
+    fn snapshot(issues: Vec<TriageItem>) -> Snapshot {
+        Snapshot { collected_at: datetime!(2024-11-08 0:00 UTC), issues }
+    }
+
+    fn numbers(issues: &[&TriageItem]) -> Vec<u64> {
+        issues.iter().map(|issue| issue.number).collect()
+    }
+
+    #[test]
+    fn diffs_issues_by_number() {
+        let old = snapshot(triage_item::simplify_repr(vec![
+            test_util::issue(1, &["P-high", "T-compiler"], &["jane"]),
+            test_util::issue(2, &["P-high"], &[]),
+            test_util::issue(3, &["P-high"], &[]),
+            test_util::issue(4, &["P-high"], &[]),
+        ]));
+        let mut new_issues = triage_item::simplify_repr(vec![
+            test_util::issue(1, &["P-high", "A-parser"], &["john"]),
+            test_util::issue(3, &["P-high"], &[]),
+            test_util::issue(4, &["P-high"], &[]),
+            test_util::issue(5, &["P-high"], &[]),
+        ]);
+        new_issues[2].updated_at = datetime!(2024-11-09 0:00 UTC);
+        let new = snapshot(new_issues);
+
+        let diff = SnapshotDiff::new(&old, &new);
+
+        assert_eq!(numbers(&diff.new), [5]);
+        assert_eq!(numbers(&diff.gone), [2]);
+        assert_eq!(numbers(&diff.updated), [4]);
+        let [change] = diff.changed.as_slice() else {
+            panic!("expected exactly one changed issue");
+        };
+        assert_eq!(change.issue.number, 1);
+        assert_eq!(change.added_labels, ["A-parser"]);
+        assert_eq!(change.removed_labels, ["T-compiler"]);
+        assert_eq!(change.added_assignees, ["john"]);
+        assert_eq!(change.removed_assignees, ["jane"]);
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        let issues = || triage_item::simplify_repr(vec![test_util::issue(1, &["P-high"], &[])]);
+        let (old, new) = (snapshot(issues()), snapshot(issues()));
+
+        let diff = SnapshotDiff::new(&old, &new);
+
+        assert!(diff.new.is_empty() && diff.gone.is_empty());
+        assert!(diff.changed.is_empty() && diff.updated.is_empty());
+    }
+}
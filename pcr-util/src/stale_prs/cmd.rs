@@ -0,0 +1,8 @@
+use super::STATUS_LABELS;
+use crate::query::IssueQuery;
+
+pub(crate) fn stale_prs_query() -> IssueQuery {
+    IssueQuery::pull_requests("PRs waiting on author or review, or blocked")
+        .sort("updated-asc")
+        .any_label(STATUS_LABELS)
+}
@@ -0,0 +1,164 @@
+use color_eyre::Result as EResult;
+use time::OffsetDateTime;
+
+use super::{STALENESS_BUCKETS, cmd};
+use crate::TriageItem;
+use crate::config::CommonTriageConfig;
+use crate::markdown_stub::{IssueField, IssueLayout, RenderCtxt};
+use crate::team::TeamTaxonomy;
+
+/// Assignees of pull requests are their reviewers.
+const FIELDS: &[IssueField] = &[
+    IssueField::Link,
+    IssueField::Status,
+    IssueField::Author,
+    IssueField::Assignees,
+    IssueField::ReviewState,
+    IssueField::Labels,
+];
+
+pub(crate) struct ReviewInfo<'a> {
+    pub(crate) prs: &'a [TriageItem],
+    pub(crate) collected_at: OffsetDateTime,
+}
+
+impl<'a> ReviewInfo<'a> {
+    pub(crate) fn new(prs: &'a [TriageItem], collected_at: OffsetDateTime) -> Self {
+        Self { prs, collected_at }
+    }
+
+    /// Pull requests bucketed by [`STALENESS_BUCKETS`], with each bucket's lower bound in days.
+    /// From the stalest bucket down, each bucket ends where the previous one starts.
+    pub(crate) fn staleness_buckets(&'a self) -> Vec<(i64, Vec<&'a TriageItem>)> {
+        let mut max_days = None;
+        STALENESS_BUCKETS
+            .iter()
+            .map(|&min_days| {
+                let prs = self.in_status_for(min_days, max_days);
+                max_days = Some(min_days);
+                (min_days, prs)
+            })
+            .collect()
+    }
+
+    /// Pull requests that have had their status for more than `min_days` but at most `max_days`,
+    /// longest first.
+    fn in_status_for(&'a self, min_days: i64, max_days: Option<i64>) -> Vec<&'a TriageItem> {
+        let mut prs = self
+            .prs
+            .iter()
+            .filter(|pr| {
+                self.status_days(pr)
+                    .is_some_and(|days| days > min_days && max_days.is_none_or(|max| days <= max))
+            })
+            .collect::<Vec<_>>();
+        prs.sort_by_key(|pr| std::cmp::Reverse(self.status_days(pr)));
+        prs
+    }
+
+    /// Pull requests where we don't know since when they have their status.
+    pub(crate) fn unknown_status_age(&'a self) -> Vec<&'a TriageItem> {
+        self.prs.iter().filter(|pr| self.status_days(pr).is_none()).collect()
+    }
+
+    fn status_days(&self, pr: &TriageItem) -> Option<i64> {
+        pr.status.as_ref()?.days(self.collected_at)
+    }
+}
+
+pub(crate) fn render_markdown_stub<'a>(
+    config: &CommonTriageConfig,
+    taxonomy: &TeamTaxonomy,
+    collected_at: OffsetDateTime,
+    info: ReviewInfo<'a>,
+) -> EResult<String> {
+    let layout = config.issue_layout.unwrap_or(IssueLayout::Compact);
+    let mut ctx = RenderCtxt::new(config, taxonomy, collected_at, layout, FIELDS)?;
+    for pr in info.prs {
+        if pr.assignees.is_empty() {
+            ctx.flag_issue(pr.number, "No reviewer assigned".to_owned());
+        }
+    }
+    ctx.render_document_header()?;
+    ctx.render_query_link(&cmd::stale_prs_query())?;
+
+    for (min_days, prs) in info.staleness_buckets() {
+        ctx.render_section(
+            &format!("In status for more than {min_days} days"),
+            None,
+            Some("Did not find PRs in this staleness range"),
+            &prs,
+        )?;
+    }
+    let unknown = info.unknown_status_age();
+    if !unknown.is_empty() {
+        ctx.render_section("In status for an unknown time", None, None, &unknown)?;
+    }
+
+    Ok(ctx.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::status_label::StatusLabel;
+    use crate::{test_util, triage_item};
+
+    const NOW: OffsetDateTime = datetime!(2024-11-08 12:00 UTC);
+
+    /// Pull requests `S-waiting-on-review` for the given number of days, if known.
+    fn prs(status_days: &[(u64, Option<i64>)]) -> Vec<TriageItem> {
+        let reprs = status_days
+            .iter()
+            .map(|&(number, _)| test_util::issue(number, &["S-waiting-on-review"], &[]))
+            .collect();
+        let mut prs = triage_item::simplify_repr(reprs);
+        for (pr, &(_, days)) in prs.iter_mut().zip(status_days) {
+            pr.status = Some(StatusLabel {
+                label: "S-waiting-on-review".to_owned(),
+                since: days.map(|days| NOW - Duration::days(days)),
+            });
+        }
+        prs
+    }
+
+    fn numbers(prs: &[&TriageItem]) -> Vec<u64> {
+        prs.iter().map(|pr| pr.number).collect()
+    }
+
+    #[test]
+    fn buckets_by_days_in_status_stalest_first() {
+        let prs = prs(&[
+            (1, Some(29)),
+            (2, Some(30)),
+            (3, Some(31)),
+            (4, Some(90)),
+            (5, Some(91)),
+            (6, Some(180)),
+            (7, Some(400)),
+            (8, Some(181)),
+            (9, Some(60)),
+        ]);
+        let info = ReviewInfo::new(&prs, NOW);
+
+        let buckets = info
+            .staleness_buckets()
+            .into_iter()
+            .map(|(min_days, prs)| (min_days, numbers(&prs)))
+            .collect::<Vec<_>>();
+        assert_eq!(buckets, [(180, vec![7, 8]), (90, vec![6, 5]), (30, vec![4, 9, 3])]);
+    }
+
+    #[test]
+    fn prs_without_label_event_have_unknown_status_age() {
+        let prs = prs(&[(1, None), (2, Some(100)), (3, None)]);
+        let info = ReviewInfo::new(&prs, NOW);
+
+        assert_eq!(numbers(&info.unknown_status_age()), [1, 3]);
+        let bucketed = info.staleness_buckets().into_iter().flat_map(|(_, prs)| numbers(&prs));
+        assert_eq!(bucketed.collect::<Vec<_>>(), [2]);
+    }
+}
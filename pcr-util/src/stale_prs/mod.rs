@@ -0,0 +1,48 @@
+mod cmd;
+mod markdown_stub;
+
+use self::markdown_stub::ReviewInfo;
+use crate::config::{CommonConfig, StalePrTriage};
+use crate::issue_source::IssueSource;
+use crate::team::TeamTaxonomy;
+use crate::triage::{self, TriageSpec};
+use crate::{EResult, status_label};
+
+/// Identifies this triage kind in the history database.
+const TRIAGE_KIND: &str = "stale-prs";
+
+/// Status labels of pull requests stuck somewhere, a pull request's status is the first it has.
+const STATUS_LABELS: &[&str] = &["S-waiting-on-author", "S-waiting-on-review", "S-blocked"];
+
+/// Pull requests are bucketed by having their status for more than this many days, stalest first.
+/// Those in their status for fewer days are left out.
+const STALENESS_BUCKETS: &[i64] = &[180, 90, 30];
+
+pub(crate) fn perform_triage(
+    config: &CommonConfig,
+    triage_config: &StalePrTriage,
+    source: &dyn IssueSource,
+) -> EResult<()> {
+    let spec = TriageSpec {
+        kind: TRIAGE_KIND,
+        description: "Stale PRs",
+        queries: &[cmd::stale_prs_query()],
+    };
+    triage::run_triage(
+        config,
+        &triage_config.common,
+        &spec,
+        source,
+        |prs| status_label::collect(config, &triage_config.common, prs, STATUS_LABELS),
+        |collected_at, prs| {
+            let review_info = ReviewInfo::new(prs, collected_at);
+            let taxonomy = TeamTaxonomy::load(config)?;
+            markdown_stub::render_markdown_stub(
+                &triage_config.common,
+                &taxonomy,
+                collected_at,
+                review_info,
+            )
+        },
+    )
+}
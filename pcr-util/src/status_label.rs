@@ -0,0 +1,248 @@
+//! Since when each item has its status label, e.g. `S-waiting-on-review`, collected by triages
+//! that bucket items by how long they have been in their status.
+//!
+//! Neither `updated_at` nor the creation date say that, since any comment or push updates an item.
+//! Instead, the most recent time the status label was added is looked up in the item's timeline.
+
+use std::collections::BTreeMap;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::*;
+
+use crate::config::{CommonConfig, CommonTriageConfig};
+use crate::{TriageItem, issue_source};
+
+const LABELED_FRAGMENT: &str = "
+fragment labeled on LabeledEvent {
+  createdAt label { name }
+}";
+
+/// Selects a page of the label events of item `number`, the latest one or the one before `cursor`.
+fn label_events_selection(number: u64, cursor: Option<&str>) -> String {
+    let before = cursor
+        .map(|cursor| format!(", before: {}", serde_json::Value::from(cursor)))
+        .unwrap_or_default();
+    let timeline = format!(
+        "timelineItems(itemTypes: [LABELED_EVENT], last: 100{before}) {{ \
+        pageInfo {{ hasPreviousPage startCursor }} nodes {{ ...labeled }} }}"
+    );
+    format!(
+        "issueOrPullRequest(number: {number}) {{ \
+        ... on Issue {{ {timeline} }} ... on PullRequest {{ {timeline} }} }}"
+    )
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StatusLabel {
+    pub label: String,
+    /// When the label was last added, unknown when replaying a recording.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub since: Option<OffsetDateTime>,
+}
+
+impl StatusLabel {
+    /// Whole days between adding the label and `now`.
+    pub(crate) fn days(&self, now: OffsetDateTime) -> Option<i64> {
+        self.since.map(|since| (now - since).whole_days())
+    }
+}
+
+/// Response format.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemRepr {
+    timeline_items: TimelinePageRepr,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelinePageRepr {
+    page_info: PageInfoRepr,
+    nodes: Vec<LabeledEventRepr>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfoRepr {
+    has_previous_page: bool,
+    start_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LabeledEventRepr {
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    label: LabelRepr,
+}
+
+#[derive(Deserialize)]
+struct LabelRepr {
+    name: String,
+}
+
+/// Collect since when each item has the first of `status_labels` it has. Items with none of them
+/// are left alone.
+///
+/// Recordings only contain the `gh` list response, so there are no label events to look up when
+/// replaying one, and how long items have their status is unknown.
+pub(crate) fn collect(
+    config: &CommonConfig,
+    triage_config: &CommonTriageConfig,
+    items: &mut [TriageItem],
+    status_labels: &[&str],
+) -> EResult<()> {
+    let _sp = span!(Level::INFO, "Collecting status label events").entered();
+    let labels = items
+        .iter()
+        .filter_map(|item| Some((item.number, status_label(item, status_labels)?)))
+        .collect::<BTreeMap<_, _>>();
+    let mut since = if triage_config.from_recording.is_some() {
+        warn!("Not collecting status label events, recordings don't contain them");
+        BTreeMap::new()
+    } else {
+        info!("Downloading label events of {} items via `gh` cli", labels.len());
+        labeled_at(config.checkout()?, &labels).wrap_err("failed to collect label events")?
+    };
+    for item in items {
+        if let Some(label) = labels.get(&item.number) {
+            item.status =
+                Some(StatusLabel { label: label.to_string(), since: since.remove(&item.number) });
+        }
+    }
+    Ok(())
+}
+
+/// The first of `status_labels` that `item` has.
+fn status_label<'a>(item: &TriageItem, status_labels: &[&'a str]) -> Option<&'a str> {
+    status_labels.iter().copied().find(|&label| item.labels.iter().any(|l| l == label))
+}
+
+/// Where the search for when an item's label was last added stands after a page of label events.
+#[derive(Debug, PartialEq, Eq)]
+enum LabelSearch {
+    Found(OffsetDateTime),
+    /// Continue with the earlier page before this cursor.
+    Earlier(String),
+    /// There are no earlier pages.
+    NotFound,
+}
+
+/// The latest time `label` was added in `page`, or else the cursor of the earlier page.
+fn search_page(page: TimelinePageRepr, label: &str) -> LabelSearch {
+    let labeled_at = page
+        .nodes
+        .into_iter()
+        .filter(|event| event.label.name == label)
+        .map(|event| event.created_at)
+        .max();
+    match (labeled_at, page.page_info) {
+        (Some(labeled_at), _) => LabelSearch::Found(labeled_at),
+        (None, PageInfoRepr { has_previous_page: true, start_cursor: Some(cursor) }) => {
+            LabelSearch::Earlier(cursor)
+        }
+        (None, _) => LabelSearch::NotFound,
+    }
+}
+
+/// When the label of each item in `labels` was last added, going back through the item's label
+/// events a page at a time until finding it.
+fn labeled_at(
+    repo_path: &Utf8Path,
+    labels: &BTreeMap<u64, &str>,
+) -> EResult<BTreeMap<u64, OffsetDateTime>> {
+    let mut since = BTreeMap::new();
+    // The items whose label is yet to be found, and the cursor of their next page.
+    let mut cursors = labels.keys().map(|&number| (number, None)).collect::<BTreeMap<_, _>>();
+    while !cursors.is_empty() {
+        let numbers = cursors.keys().copied().collect::<Vec<_>>();
+        let pages = issue_source::gh_api_graphql_batched::<ItemRepr>(
+            repo_path,
+            &numbers,
+            |number| label_events_selection(number, cursors[&number].as_deref()),
+            LABELED_FRAGMENT,
+        )?;
+        cursors.clear();
+        for (number, item) in pages {
+            let label = labels[&number];
+            match search_page(item.timeline_items, label) {
+                LabelSearch::Found(labeled_at) => {
+                    since.insert(number, labeled_at);
+                }
+                LabelSearch::Earlier(cursor) => {
+                    cursors.insert(number, Some(cursor));
+                }
+                LabelSearch::NotFound => debug!("#{number} has no label events adding `{label}`"),
+            }
+        }
+        if !cursors.is_empty() {
+            debug!("Downloading earlier label events of {} items", cursors.len());
+        }
+    }
+    Ok(since)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{test_util, triage_item};
+
+    fn page(labels: &[(&str, &str)], start_cursor: Option<&str>) -> TimelinePageRepr {
+        let nodes = labels
+            .iter()
+            .map(|(label, created_at)| json!({ "createdAt": created_at, "label": { "name": label } }))
+            .collect::<Vec<_>>();
+        serde_json::from_value(json!({
+            "pageInfo": { "hasPreviousPage": start_cursor.is_some(), "startCursor": start_cursor },
+            "nodes": nodes,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn first_status_label_wins() {
+        let status_labels = ["S-waiting-on-author", "S-waiting-on-review", "S-blocked"];
+        let items = triage_item::simplify_repr(vec![
+            test_util::issue(1, &["S-blocked", "S-waiting-on-review", "T-compiler"], &[]),
+            test_util::issue(2, &["S-blocked"], &[]),
+            test_util::issue(3, &["T-compiler"], &[]),
+        ]);
+        let labels =
+            items.iter().map(|item| status_label(item, &status_labels)).collect::<Vec<_>>();
+        assert_eq!(labels, [Some("S-waiting-on-review"), Some("S-blocked"), None]);
+    }
+
+    #[test]
+    fn finds_latest_time_label_was_added() {
+        let page = page(
+            &[
+                ("S-waiting-on-review", "2024-01-01T00:00:00Z"),
+                ("A-parser", "2024-03-01T00:00:00Z"),
+                ("S-waiting-on-review", "2024-02-01T00:00:00Z"),
+            ],
+            Some("cursor"),
+        );
+        assert_eq!(
+            search_page(page, "S-waiting-on-review"),
+            LabelSearch::Found(datetime!(2024-02-01 0:00 UTC))
+        );
+    }
+
+    #[test]
+    fn continues_with_earlier_page_until_there_is_none() {
+        let labeled_other = [("A-parser", "2024-03-01T00:00:00Z")];
+        assert_eq!(
+            search_page(page(&labeled_other, Some("cursor")), "S-blocked"),
+            LabelSearch::Earlier("cursor".to_owned())
+        );
+        assert_eq!(search_page(page(&labeled_other, None), "S-blocked"), LabelSearch::NotFound);
+    }
+}
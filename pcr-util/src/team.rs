@@ -0,0 +1,155 @@
+//! Which labels denote teams and working groups.
+//!
+//! Queries, browser links and ownership checks all go through [`TeamTaxonomy`], so they agree on
+//! what e.g. "has no team label" means.
+
+use std::collections::BTreeSet;
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use serde::Deserialize;
+use tracing::*;
+
+use crate::config::CommonConfig;
+
+const BUILTIN_TEAMS: &[&str] = &[
+    "T-bootstrap",
+    "T-cargo",
+    "T-clippy",
+    "T-community",
+    "T-compiler",
+    "T-core",
+    "T-crates-io",
+    "T-dev-tools",
+    "T-docs-rs",
+    "T-infra",
+    "T-lang",
+    "T-lang-docs",
+    "T-leadership-council",
+    "T-libs",
+    "T-libs-api",
+    "T-miri",
+    "T-opsem",
+    "T-release",
+    "T-rust-analyzer",
+    "T-rustdoc",
+    "T-rustdoc-frontend",
+    "T-rustfmt",
+    "T-spec",
+    "T-style",
+    "T-types",
+];
+
+const BUILTIN_WORKING_GROUPS: &[&str] = &[
+    "WG-async",
+    "WG-compiler-performance",
+    "WG-const-eval",
+    "WG-debugging",
+    "WG-diagnostics",
+    "WG-embedded",
+    "WG-incr-comp",
+    "WG-llvm",
+    "WG-mir-opt",
+    "WG-trait-system-refactor",
+];
+
+/// Team and working group labels, e.g. loaded from a TOML file like
+///
+/// ```toml
+/// teams = ["T-compiler", "T-lang"]
+/// working-groups = ["WG-llvm"]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct TeamTaxonomy {
+    teams: BTreeSet<String>,
+    working_groups: BTreeSet<String>,
+}
+
+impl TeamTaxonomy {
+    /// Use the taxonomy file given by `--team-taxonomy` if any. Otherwise, use the built-in
+    /// taxonomy extended with the team and working group labels from the checkout's
+    /// `triagebot.toml`, if a checkout is given.
+    pub(crate) fn load(config: &CommonConfig) -> EResult<Self> {
+        if let Some(path) = &config.team_taxonomy {
+            info!("Loading team taxonomy from `{path}`");
+            let taxonomy = fs::read_to_string(path)
+                .wrap_err_with(|| format!("failed to read team taxonomy from `{path}`"))?;
+            return toml::from_str(&taxonomy)
+                .wrap_err_with(|| format!("failed to parse team taxonomy from `{path}`"));
+        }
+
+        let mut taxonomy = Self::builtin();
+        let Some(repo_path) = &config.repo_path else {
+            warn!("No checkout given, only using built-in team taxonomy");
+            return Ok(taxonomy);
+        };
+        let triagebot_toml = repo_path.join("triagebot.toml");
+        if triagebot_toml.exists() {
+            taxonomy.extend_from_triagebot_toml(&triagebot_toml)?;
+        } else {
+            warn!("`{triagebot_toml}` does not exist, only using built-in team taxonomy");
+        }
+        Ok(taxonomy)
+    }
+
+    /// The built-in team and working group labels only.
+    pub(crate) fn builtin() -> Self {
+        Self {
+            teams: BUILTIN_TEAMS.iter().map(|&team| team.to_owned()).collect(),
+            working_groups: BUILTIN_WORKING_GROUPS.iter().map(|&wg| wg.to_owned()).collect(),
+        }
+    }
+
+    /// `triagebot.toml` has no list of teams, but `[autolabel."T-*"]` tables tell us about team
+    /// and working group labels in use.
+    fn extend_from_triagebot_toml(&mut self, path: &Utf8Path) -> EResult<()> {
+        debug!("Extending team taxonomy from `{path}`");
+        let triagebot = fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read `{path}`"))?
+            .parse::<toml::Table>()
+            .wrap_err_with(|| format!("failed to parse `{path}`"))?;
+        let Some(autolabels) = triagebot.get("autolabel").and_then(toml::Value::as_table) else {
+            return Ok(());
+        };
+        for label in autolabels.keys() {
+            if label.starts_with("T-") {
+                self.teams.insert(label.clone());
+            } else if is_working_group_like(label) {
+                self.working_groups.insert(label.clone());
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn teams(&self) -> impl Iterator<Item = &str> {
+        self.teams.iter().map(String::as_str)
+    }
+
+    pub(crate) fn working_groups(&self) -> impl Iterator<Item = &str> {
+        self.working_groups.iter().map(String::as_str)
+    }
+
+    pub(crate) fn is_team(&self, label: &str) -> bool {
+        self.teams.contains(label)
+    }
+
+    /// Working group labels are not consistently cased, e.g. `wg-debugging`.
+    pub(crate) fn is_working_group(&self, label: &str) -> bool {
+        self.working_groups.iter().any(|wg| wg.eq_ignore_ascii_case(label))
+    }
+
+    pub(crate) fn has_team(&self, labels: &[String]) -> bool {
+        labels.iter().any(|label| self.is_team(label))
+    }
+
+    pub(crate) fn has_working_group(&self, labels: &[String]) -> bool {
+        labels.iter().any(|label| self.is_working_group(label))
+    }
+}
+
+fn is_working_group_like(label: &str) -> bool {
+    label.get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("WG-"))
+}
@@ -0,0 +1,287 @@
+//! User-supplied Markdown stub templates.
+//!
+//! A template is a [minijinja](https://docs.rs/minijinja) file which may define any of these
+//! macros, falling back to the built-in rendering for those it doesn't define:
+//!
+//! - `header(title, collected_at)`: the document header.
+//! - `section(heading, link, issue_count)`: a section heading, `link` is the URL of the section's
+//!   GitHub search results if there is one.
+//! - `issue(issue)`: an issue, see [`IssueContext`] for its attributes.
+//!
+//! See `stub-template.example.md.jinja` for an example.
+
+use std::fs;
+
+use camino::Utf8Path;
+use color_eyre::Result as EResult;
+use color_eyre::eyre::Context;
+use minijinja::{Captured, Environment, Value};
+use serde::Serialize;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::feature_activity::FeatureActivity;
+use crate::feature_gates::{FeatureGate, FeatureGates};
+use crate::feature_usage::FeatureUsage;
+use crate::notes::TriageNote;
+use crate::{TriageItem, linked_prs};
+
+const TEMPLATE_NAME: &str = "stub";
+
+pub(crate) struct StubTemplate {
+    env: Environment<'static>,
+}
+
+impl StubTemplate {
+    pub(crate) fn load(path: &Utf8Path) -> EResult<Self> {
+        let source = fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read template `{path}`"))?;
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+        env.add_template_owned(TEMPLATE_NAME, source)
+            .wrap_err_with(|| format!("failed to parse template `{path}`"))?;
+        let template = Self { env };
+        // Report errors in the top-level content now rather than at the first macro call.
+        template.render().wrap_err_with(|| format!("failed to render template `{path}`"))?;
+        Ok(template)
+    }
+
+    /// Top-level template content is ignored, we only care about the macros it defines. Rendering
+    /// the template gives us a state to call them in.
+    fn render(&self) -> EResult<Captured<'_>> {
+        Ok(self.env.get_template(TEMPLATE_NAME)?.render_captured(())?)
+    }
+
+    /// Render with the template's macro `name`, or return `None` if it doesn't define one.
+    pub(crate) fn render_macro(&self, name: &str, args: &[Value]) -> EResult<Option<String>> {
+        let captured = self.render()?;
+        let state = captured.state();
+        if state.lookup(name).is_none() {
+            return Ok(None);
+        }
+        let rendered = state
+            .call_macro(name, args)
+            .wrap_err_with(|| format!("failed to render template macro `{name}`"))?;
+        Ok(Some(rendered))
+    }
+}
+
+/// What an `issue` template macro gets to see of an issue.
+#[derive(Serialize)]
+pub(crate) struct IssueContext<'a> {
+    pub(crate) number: u64,
+    pub(crate) title: &'a str,
+    pub(crate) url: &'a str,
+    pub(crate) author: &'a str,
+    pub(crate) labels: &'a [String],
+    pub(crate) working_groups: Vec<&'a str>,
+    pub(crate) assignees: &'a [String],
+    /// Feature gates tracked by the issue, if the triage kind looks them up in the checkout.
+    pub(crate) feature_gates: Vec<FeatureGateContext<'a>>,
+    /// Triage kind specific remarks, e.g. why the issue needs attention.
+    pub(crate) flags: &'a [String],
+    /// RFC 3339 timestamp.
+    pub(crate) created_at: String,
+    /// RFC 3339 timestamp.
+    pub(crate) updated_at: String,
+    /// Days between issue creation and collecting the issue.
+    pub(crate) age_days: i64,
+    /// Days between the last issue update and collecting the issue.
+    pub(crate) days_since_update: i64,
+    /// When the issue was last triaged (`YYYY-MM-DD`), if `--notes-path` has notes for it.
+    pub(crate) last_reviewed_on: Option<String>,
+    pub(crate) last_decision: Option<&'a str>,
+    pub(crate) last_notes: Option<&'a str>,
+    /// Total number of comments, if `--latest-comments` collected them.
+    pub(crate) comment_count: Option<u64>,
+    /// The latest comments, newest first.
+    pub(crate) latest_comments: Vec<CommentContext<'a>>,
+    /// Pull requests linked to the issue, if `--linked-prs` collected them.
+    pub(crate) linked_prs: Option<Vec<LinkedPrContext<'a>>>,
+    /// Whether a pull request closing the issue was merged, although the issue is still open.
+    pub(crate) has_merged_fix: bool,
+    /// Whether the notes should start with a `Proposed priority: **TODO**` slot.
+    pub(crate) proposed_priority_slot: bool,
+    /// What only pull requests have, if the item is one.
+    pub(crate) pull_request: Option<PullRequestContext<'a>>,
+    /// Status label, if the triage kind collected since when the item has it.
+    pub(crate) status: Option<&'a str>,
+    /// Days the item has had its status label, if known.
+    pub(crate) status_days: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct FeatureGateContext<'a> {
+    pub(crate) name: &'a str,
+    /// `unstable`, `incomplete`, `internal`, `accepted` or `removed`.
+    pub(crate) status: &'static str,
+    /// Version the feature was added, accepted or removed in, depending on its status.
+    pub(crate) since: &'a str,
+    /// In-tree usage, if `--feature-usage` collected it.
+    pub(crate) usage: Option<&'a FeatureUsage>,
+    /// Git activity, if `--feature-git-activity` collected it.
+    pub(crate) activity: Option<&'a FeatureActivity>,
+}
+
+impl<'a> FeatureGateContext<'a> {
+    pub(crate) fn new(gate: &'a FeatureGate, feature_gates: &'a FeatureGates) -> Self {
+        Self {
+            name: &gate.name,
+            status: gate.status.name(),
+            since: &gate.since,
+            usage: feature_gates.usage(&gate.name),
+            activity: feature_gates.activity(&gate.name),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct LinkedPrContext<'a> {
+    pub(crate) number: u64,
+    pub(crate) title: &'a str,
+    /// `open`, `closed` or `merged`.
+    pub(crate) state: &'static str,
+    pub(crate) is_draft: bool,
+    pub(crate) author: &'a str,
+    /// RFC 3339 timestamp.
+    pub(crate) updated_at: String,
+    pub(crate) url: &'a str,
+    pub(crate) closes_issue: bool,
+}
+
+#[derive(Serialize)]
+pub(crate) struct PullRequestContext<'a> {
+    pub(crate) is_draft: bool,
+    /// `approved`, `changes requested` or `review required`, if there is a review decision.
+    pub(crate) review_decision: Option<&'static str>,
+    pub(crate) review_requests: &'a [String],
+    /// `mergeable`, `conflicting` or `unknown`.
+    pub(crate) mergeable: &'static str,
+    pub(crate) head_ref_name: &'a str,
+}
+
+#[derive(Serialize)]
+pub(crate) struct CommentContext<'a> {
+    pub(crate) author: &'a str,
+    /// RFC 3339 timestamp.
+    pub(crate) created_at: String,
+    pub(crate) url: &'a str,
+    pub(crate) body: &'a str,
+}
+
+impl<'a> IssueContext<'a> {
+    pub(crate) fn new(
+        issue: &'a TriageItem,
+        working_groups: Vec<&'a str>,
+        feature_gates: Vec<FeatureGateContext<'a>>,
+        flags: &'a [String],
+        last_review: Option<&'a TriageNote>,
+        collected_at: OffsetDateTime,
+    ) -> EResult<Self> {
+        Ok(Self {
+            number: issue.number,
+            title: &issue.title,
+            url: &issue.url,
+            author: &issue.author,
+            labels: &issue.labels,
+            working_groups,
+            assignees: &issue.assignees,
+            feature_gates,
+            flags,
+            created_at: issue.created_at.format(&Rfc3339)?,
+            updated_at: issue.updated_at.format(&Rfc3339)?,
+            age_days: (collected_at - issue.created_at).whole_days(),
+            days_since_update: (collected_at - issue.updated_at).whole_days(),
+            last_reviewed_on: last_review.map(|review| review.reviewed_on.to_string()),
+            last_decision: last_review.and_then(|review| review.decision.as_deref()),
+            last_notes: last_review.map(|review| review.notes.as_str()),
+            comment_count: issue.activity.as_ref().map(|activity| activity.comment_count),
+            latest_comments: issue
+                .activity
+                .iter()
+                .flat_map(|activity| activity.latest_comments.iter().rev())
+                .map(|comment| {
+                    Ok(CommentContext {
+                        author: &comment.author,
+                        created_at: comment.created_at.format(&Rfc3339)?,
+                        url: &comment.url,
+                        body: &comment.body,
+                    })
+                })
+                .collect::<EResult<_>>()?,
+            linked_prs: issue
+                .linked_prs
+                .as_ref()
+                .map(|linked_prs| {
+                    linked_prs
+                        .iter()
+                        .map(|pr| {
+                            Ok(LinkedPrContext {
+                                number: pr.number,
+                                title: &pr.title,
+                                state: pr.state.name(),
+                                is_draft: pr.is_draft,
+                                author: &pr.author,
+                                updated_at: pr.updated_at.format(&Rfc3339)?,
+                                url: &pr.url,
+                                closes_issue: pr.closes_issue,
+                            })
+                        })
+                        .collect::<EResult<_>>()
+                })
+                .transpose()?,
+            has_merged_fix: issue.linked_prs.as_deref().is_some_and(linked_prs::has_merged_fix),
+            proposed_priority_slot: false,
+            status: issue.status.as_ref().map(|status| status.label.as_str()),
+            status_days: issue.status.as_ref().and_then(|status| status.days(collected_at)),
+            pull_request: issue.pull_request.as_ref().map(|pr| PullRequestContext {
+                is_draft: pr.is_draft,
+                review_decision: pr.review_decision.map(|decision| decision.name()),
+                review_requests: &pr.review_requests,
+                mergeable: pr.mergeable.name(),
+                head_ref_name: &pr.head_ref_name,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::config::PHighTriage;
+    use crate::issue_source::InMemorySource;
+    use crate::p_high;
+    use crate::test_util::{self, TestDir};
+
+    #[test]
+    fn falls_back_to_built_in_rendering_for_undefined_macros() {
+        let dir = TestDir::new("stub-template");
+        let (config, mut common) = test_util::configs(&dir);
+        let template = dir.path().join("template.md.jinja");
+        fs::write(
+            &template,
+            "Ignored top-level content.\n\
+            {% macro issue(issue) %}\n\
+            ### #{{ issue.number }}: {{ issue.title }} by {{ issue.author }}\n\
+            \n\
+            <!-- notes -->\n\
+            **TODO**\n\
+            \n\
+            {% endmacro %}\n",
+        )
+        .unwrap();
+        common.template = Some(template);
+        let source = InMemorySource::new(vec![test_util::issue(1, &["P-high"], &[])]);
+
+        p_high::perform_triage(&config, &PHighTriage { common }, &source).unwrap();
+
+        let stub = dir.read("stub.md");
+        assert!(stub.starts_with("<!-- stubs generated with pcr-util -->\n\n# Test triage\n"));
+        assert!(stub.contains("## P-high missing team label\n"));
+        assert!(stub.contains("### #1: Issue 1 by author\n\n<!-- notes -->\n**TODO**\n"));
+        assert!(!stub.contains("Ignored top-level content."));
+    }
+}
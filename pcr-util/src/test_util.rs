@@ -0,0 +1,82 @@
+//! Helpers for running triages in tests, without GitHub access.
+
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde_json::json;
+
+use crate::config::{CommonConfig, CommonTriageConfig, LogLevel};
+use crate::triage_item::TriageItemRepr;
+
+/// A directory for a test's output files, removed when dropped.
+pub(crate) struct TestDir {
+    path: Utf8PathBuf,
+}
+
+impl TestDir {
+    /// `name` must be unique among tests.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = Utf8PathBuf::try_from(std::env::temp_dir())
+            .expect("temporary directory path is UTF-8")
+            .join(format!("pcr-util-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("failed to create test directory");
+        Self { path }
+    }
+
+    pub(crate) fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    pub(crate) fn read(&self, file: &str) -> String {
+        fs::read_to_string(self.path.join(file)).expect("failed to read test output")
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Configs using the built-in team taxonomy, writing `snapshot.json` and `stub.md` to `dir`.
+pub(crate) fn configs(dir: &TestDir) -> (CommonConfig, CommonTriageConfig) {
+    let config = CommonConfig {
+        repo_path: Some(dir.path().to_owned()),
+        team_taxonomy: None,
+        log_level: LogLevel::Info,
+    };
+    let triage_config = CommonTriageConfig {
+        persist_path: Some(dir.path().join("snapshot.json")),
+        markdown_stub_title: "Test triage".to_owned(),
+        markdown_stub_path: dir.path().join("stub.md"),
+        issue_layout: None,
+        template: None,
+        notes_path: None,
+        limit: None,
+        latest_comments: None,
+        linked_prs: false,
+        feature_gates: false,
+        feature_usage: false,
+        feature_git_activity: false,
+        history_db: None,
+        from_recording: None,
+        from_snapshot: None,
+    };
+    (config, triage_config)
+}
+
+/// An issue like `gh issue list --json` returns it.
+pub(crate) fn issue(number: u64, labels: &[&str], assignees: &[&str]) -> TriageItemRepr {
+    serde_json::from_value(json!({
+        "assignees": assignees.iter().map(|login| json!({ "login": login })).collect::<Vec<_>>(),
+        "author": { "login": "author" },
+        "createdAt": "2024-11-06T02:24:31Z",
+        "labels": labels.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        "number": number,
+        "title": format!("Issue {number}"),
+        "updatedAt": "2024-11-08T05:00:16Z",
+        "url": format!("https://github.com/rust-lang/rust/issues/{number}"),
+    }))
+    .expect("valid issue")
+}
@@ -0,0 +1,196 @@
+//! The pipeline shared by all triage kinds: collect issues (or load a previously persisted
+//! snapshot), persist them, record them in the history, and write the Markdown stub.
+
+use std::fs;
+
+use color_eyre::Result as EResult;
+use color_eyre::eyre::{Context, eyre};
+use itertools::Itertools as _;
+use time::OffsetDateTime;
+use tracing::*;
+
+use crate::config::{CommonConfig, CommonTriageConfig};
+use crate::issue_source::IssueSource;
+use crate::query::IssueQuery;
+use crate::{TriageItem, activity, history, linked_prs, snapshot, triage_item};
+
+pub(crate) struct TriageSpec<'a> {
+    /// Identifies the triage kind in the history database.
+    pub(crate) kind: &'a str,
+    /// What the triage collects, for log messages.
+    pub(crate) description: &'a str,
+    /// The results of all queries are collected, an item matching several queries only once.
+    pub(crate) queries: &'a [IssueQuery],
+}
+
+/// Collect the issues matching `spec`'s queries from `source` sorted from oldest to newest, run
+/// the kind-specific `collect` step on them, and write the Markdown stub returned by `render`.
+///
+/// When re-rendering from a snapshot, neither GitHub is queried nor `collect` is run.
+pub(crate) fn run_triage(
+    config: &CommonConfig,
+    common: &CommonTriageConfig,
+    spec: &TriageSpec<'_>,
+    source: &dyn IssueSource,
+    collect: impl FnOnce(&mut Vec<TriageItem>) -> EResult<()>,
+    render: impl FnOnce(OffsetDateTime, &[TriageItem]) -> EResult<String>,
+) -> EResult<()> {
+    if let Some(snapshot_path) = &common.from_snapshot {
+        info!("Loading {} snapshot from `{snapshot_path}`", spec.description);
+        let snapshot = snapshot::load(snapshot_path)?;
+        info!("{} count: {}", spec.description, snapshot.issues.len());
+        return write_markdown_stub(common, snapshot.collected_at, &snapshot.issues, render);
+    }
+
+    let persist_path = common.persist_path.as_ref().ok_or_else(|| {
+        eyre!("`--persist-path` is required unless re-rendering with `--from-snapshot`")
+    })?;
+    let issues = {
+        let _sp = span!(Level::INFO, "Collecting issues", kind = spec.kind).entered();
+        let mut issues = vec![];
+        for query in spec.queries {
+            issues.extend(triage_item::simplify_repr(source.fetch(query)?));
+        }
+        // E.g. a pull request can be both nominated for discussion and for a backport.
+        let mut issues = issues.into_iter().unique_by(|issue| issue.number).collect::<Vec<_>>();
+        activity::collect_if_requested(config, common, &mut issues)?;
+        linked_prs::collect_if_requested(config, common, &mut issues)?;
+
+        // Intentionally sort by oldest to newest.
+        issues.sort_by_key(|issue| issue.number);
+        collect(&mut issues)?;
+        issues
+    };
+
+    let collected_at = OffsetDateTime::now_utc();
+    info!("{} count: {}", spec.description, issues.len());
+    info!("Writing {} metadata json to `{persist_path}`", spec.description);
+    snapshot::save(persist_path, collected_at, &issues)?;
+
+    history::record_if_requested(common, spec.kind, collected_at, &issues)?;
+
+    write_markdown_stub(common, collected_at, &issues, render)
+}
+
+fn write_markdown_stub(
+    common: &CommonTriageConfig,
+    collected_at: OffsetDateTime,
+    issues: &[TriageItem],
+    render: impl FnOnce(OffsetDateTime, &[TriageItem]) -> EResult<String>,
+) -> EResult<()> {
+    let stub = render(collected_at, issues).wrap_err("failed to render markdown stub")?;
+    info!("Writing markdown stub to `{}`", common.markdown_stub_path);
+    fs::write(&common.markdown_stub_path, &stub).wrap_err_with(|| {
+        format!("failed to write markdown stub to `{}`", common.markdown_stub_path)
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue_source::InMemorySource;
+    use crate::test_util::{self, TestDir};
+    use crate::triage_item::TriageItemRepr;
+
+    /// Fails the test if the triage queries GitHub.
+    struct UnreachableSource;
+
+    impl IssueSource for UnreachableSource {
+        fn fetch(&self, query: &IssueQuery) -> EResult<Vec<TriageItemRepr>> {
+            Err(eyre!("unexpectedly fetched {}", query.description))
+        }
+    }
+
+    fn render_numbers(_: OffsetDateTime, issues: &[TriageItem]) -> EResult<String> {
+        Ok(issues.iter().map(|issue| format!("#{}\n", issue.number)).collect())
+    }
+
+    #[test]
+    fn collects_unique_items_sorted_by_number() {
+        let dir = TestDir::new("collects-unique-items");
+        let (config, triage_config) = test_util::configs(&dir);
+        let source =
+            InMemorySource::new(vec![test_util::issue(3, &[], &[]), test_util::issue(1, &[], &[])]);
+        let queries = [IssueQuery::new("issues"), IssueQuery::new("more issues")];
+        let spec = TriageSpec { kind: "test", description: "Test issues", queries: &queries };
+
+        run_triage(&config, &triage_config, &spec, &source, |_| Ok(()), render_numbers).unwrap();
+
+        assert_eq!(dir.read("stub.md"), "#1\n#3\n");
+        let snapshot = snapshot::load(&dir.path().join("snapshot.json")).unwrap();
+        let numbers = snapshot.issues.iter().map(|issue| issue.number).collect::<Vec<_>>();
+        assert_eq!(numbers, [1, 3]);
+    }
+
+    #[test]
+    fn kind_specific_collect_step_runs_after_sorting() {
+        let dir = TestDir::new("collect-step");
+        let (config, triage_config) = test_util::configs(&dir);
+        let source = InMemorySource::new(vec![
+            test_util::issue(2, &[], &[]),
+            test_util::issue(1, &["T-compiler"], &[]),
+            test_util::issue(3, &[], &[]),
+        ]);
+        let queries = [IssueQuery::new("issues")];
+        let spec = TriageSpec { kind: "test", description: "Test issues", queries: &queries };
+
+        run_triage(
+            &config,
+            &triage_config,
+            &spec,
+            &source,
+            |issues| {
+                issues.retain(|issue| issue.labels.is_empty());
+                issues.reverse();
+                Ok(())
+            },
+            render_numbers,
+        )
+        .unwrap();
+
+        assert_eq!(dir.read("stub.md"), "#3\n#2\n");
+    }
+
+    #[test]
+    fn rerenders_from_snapshot_without_fetching() {
+        let dir = TestDir::new("rerenders-from-snapshot");
+        let (config, mut triage_config) = test_util::configs(&dir);
+        let queries = [IssueQuery::new("issues")];
+        let spec = TriageSpec { kind: "test", description: "Test issues", queries: &queries };
+        let source = InMemorySource::new(vec![test_util::issue(7, &[], &[])]);
+        run_triage(&config, &triage_config, &spec, &source, |_| Ok(()), render_numbers).unwrap();
+        fs::remove_file(&triage_config.markdown_stub_path).unwrap();
+
+        triage_config.from_snapshot = triage_config.persist_path.take();
+        run_triage(
+            &config,
+            &triage_config,
+            &spec,
+            &UnreachableSource,
+            |_| panic!("collect step ran on a snapshot"),
+            render_numbers,
+        )
+        .unwrap();
+
+        assert_eq!(dir.read("stub.md"), "#7\n");
+        // Only the snapshot and the re-rendered stub, nothing else was persisted.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn snapshot_records_collection_time() {
+        let dir = TestDir::new("snapshot-collection-time");
+        let (config, triage_config) = test_util::configs(&dir);
+        let queries = [IssueQuery::new("issues")];
+        let spec = TriageSpec { kind: "test", description: "Test issues", queries: &queries };
+        let source = InMemorySource::new(vec![test_util::issue(7, &[], &[])]);
+        let before = OffsetDateTime::now_utc();
+        run_triage(&config, &triage_config, &spec, &source, |_| Ok(()), render_numbers).unwrap();
+
+        let snapshot = snapshot::load(&dir.path().join("snapshot.json")).unwrap();
+        assert!(
+            before <= snapshot.collected_at && snapshot.collected_at <= OffsetDateTime::now_utc()
+        );
+    }
+}
@@ -0,0 +1,279 @@
+// Each issue returned from `gh` cli looks like this, pull requests have a few more fields:
+/*
+{
+    "assignees": [
+        {
+            "id": "MDQ6VXNlcjM2NzQzMTQ=",
+            "login": "compiler-errors",
+            "name": "Michael Goulet"
+        }
+    ],
+    "author": {
+        "id": "MDQ6VXNlcjUyOTYyNTM0",
+        "is_bot": false,
+        "login": "wxie7",
+        "name": ""
+    },
+    "createdAt": "2024-11-06T02:24:31Z",
+    "labels": [
+        {
+            "id": "MDU6TGFiZWwyMzU3OTE=",
+            "name": "A-diagnostics",
+            "description": "Area: Messages for errors, warnings, and lints",
+            "color": "f7e101"
+        },
+        {
+            "id": "MDU6TGFiZWwyMDM0MjkyMDA=",
+            "name": "P-high",
+            "description": "High priority",
+            "color": "eb6420"
+        },
+        {
+            "id": "MDU6TGFiZWwyMTE2NjgxMDA=",
+            "name": "T-compiler",
+            "description": "Relevant to the compiler team, which will review and decide on the PR/issue.",
+            "color": "bfd4f2"
+        },
+        {
+            "id": "MDU6TGFiZWwyNjIyNTI4NDA=",
+            "name": "regression-from-stable-to-stable",
+            "description": "Performance or correctness regression from one stable version to another.",
+            "color": "e4008a"
+        },
+        {
+            "id": "MDU6TGFiZWw2NTA3MzE2NjM=",
+            "name": "C-bug",
+            "description": "Category: This is a bug.",
+            "color": "f5f1fd"
+        },
+        {
+            "id": "MDU6TGFiZWwxMTY4MDI5NTU1",
+            "name": "I-hang",
+            "description": "Issue: The compiler never terminates, due to infinite loops, deadlock, livelock, etc.",
+            "color": "e10c02"
+        },
+        {
+            "id": "LA_kwDOAAsO6M7pwtW9",
+            "name": "S-has-mcve",
+            "description": "Status: A Minimal Complete and Verifiable Example has been found for this issue",
+            "color": "862eff"
+        }
+    ],
+    "number": 132673,
+    "title": "Hang after encountering overflow errors for huge types ",
+    "updatedAt": "2024-11-08T05:00:16Z",
+    "url": "https://github.com/rust-lang/rust/issues/132673"
+},
+*/
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::activity::IssueActivity;
+use crate::linked_prs::LinkedPr;
+use crate::status_label::StatusLabel;
+
+/// Response format.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriageItemRepr {
+    pub assignees: BTreeSet<Assignee>,
+    pub author: Author,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub labels: BTreeSet<Label>,
+    pub number: u64,
+    pub title: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub url: String,
+    /// Only requested for pull requests, like the fields below.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_draft: Option<bool>,
+    /// Empty if no review is required.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_decision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_requests: Option<Vec<ReviewRequest>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mergeable: Option<Mergeable>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_ref_name: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Assignee {
+    pub login: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Author {
+    pub login: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Label {
+    pub name: String,
+}
+
+/// A user (with `login`) or a team (with `slug`) asked to review a pull request.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReviewRequest {
+    #[serde(default)]
+    pub login: String,
+    #[serde(default)]
+    pub slug: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+impl ReviewDecision {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ReviewDecision::Approved => "approved",
+            ReviewDecision::ChangesRequested => "changes requested",
+            ReviewDecision::ReviewRequired => "review required",
+        }
+    }
+}
+
+/// Whether a pull request can be merged, `Unknown` while GitHub is still computing it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Mergeable {
+    Mergeable,
+    Conflicting,
+    Unknown,
+}
+
+impl Mergeable {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Mergeable::Mergeable => "mergeable",
+            Mergeable::Conflicting => "conflicting",
+            Mergeable::Unknown => "unknown",
+        }
+    }
+}
+
+/// What only pull requests have.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PullRequestMetadata {
+    pub is_draft: bool,
+    pub review_decision: Option<ReviewDecision>,
+    /// Logins of users and slugs of teams.
+    pub review_requests: Vec<String>,
+    pub mergeable: Mergeable,
+    pub head_ref_name: String,
+}
+
+/// Our convenient format, for both issues and pull requests.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TriageItem {
+    pub assignees: Vec<String>,
+    pub author: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub labels: Vec<String>,
+    pub number: u64,
+    pub title: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub url: String,
+    /// Only collected with `--latest-comments`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<IssueActivity>,
+    /// Only collected with `--linked-prs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_prs: Option<Vec<LinkedPr>>,
+    /// Only collected by triages bucketing items by how long they have had their status label.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusLabel>,
+    /// Only for pull requests collected with `gh pr list`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequestMetadata>,
+}
+
+pub(crate) fn simplify_repr(issues: Vec<TriageItemRepr>) -> Vec<TriageItem> {
+    issues
+        .into_iter()
+        .map(
+            |TriageItemRepr {
+                 assignees,
+                 author,
+                 created_at,
+                 labels,
+                 number,
+                 title,
+                 updated_at,
+                 url,
+                 is_draft,
+                 review_decision,
+                 review_requests,
+                 mergeable,
+                 head_ref_name,
+             }| TriageItem {
+                assignees: assignees.into_iter().map(|a| a.login).collect(),
+                author: author.login,
+                labels: labels.into_iter().map(|l| l.name).collect(),
+                number,
+                title,
+                created_at,
+                updated_at,
+                url,
+                activity: None,
+                linked_prs: None,
+                status: None,
+                pull_request: is_draft.map(|is_draft| PullRequestMetadata {
+                    is_draft,
+                    review_decision: review_decision.as_deref().and_then(parse_review_decision),
+                    review_requests: review_requests
+                        .into_iter()
+                        .flatten()
+                        .map(
+                            |request| {
+                                if request.login.is_empty() { request.slug } else { request.login }
+                            },
+                        )
+                        .collect(),
+                    mergeable: mergeable.unwrap_or(Mergeable::Unknown),
+                    head_ref_name: head_ref_name.unwrap_or_default(),
+                }),
+            },
+        )
+        .collect()
+}
+
+/// `gh` reports the lack of a review decision as an empty string.
+fn parse_review_decision(decision: &str) -> Option<ReviewDecision> {
+    match decision {
+        "APPROVED" => Some(ReviewDecision::Approved),
+        "CHANGES_REQUESTED" => Some(ReviewDecision::ChangesRequested),
+        "REVIEW_REQUIRED" => Some(ReviewDecision::ReviewRequired),
+        _ => None,
+    }
+}
+
+impl TriageItem {
+    pub(crate) fn is_pull_request(&self) -> bool {
+        self.url.contains("/pull/")
+    }
+}
+
+/// Whether `label` matches `pattern`, where a trailing `*` in `pattern` matches by prefix.
+pub(crate) fn label_matches(label: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => label.starts_with(prefix),
+        None => label == pattern,
+    }
+}